@@ -0,0 +1,72 @@
+//! Times the flood fill of `expand_cell_diff_result`, run with `cargo bench --bench flood`
+use minesweeper::args::MinesweeperArgs;
+use minesweeper::cell_content::CellContent::Empty;
+use minesweeper::minesweeper::Minesweeper;
+use minesweeper::tile_visibility::TileVisibility::Hidden;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const RUNS: usize = 5;
+
+/// Median time of `RUNS` runs of `run` on a fresh `setup()`
+fn bench<T>(label: &str, setup: impl Fn() -> T, run: impl Fn(&mut T)) {
+    let mut times: Vec<Duration> = (0..RUNS)
+        .map(|_| {
+            let mut input = setup();
+            let start = Instant::now();
+            run(&mut input);
+            start.elapsed()
+        })
+        .collect();
+    times.sort();
+    println!("{label:<48} {:>10.3?}", times[RUNS / 2]);
+}
+
+fn board(side: u16, mines: u32) -> Minesweeper {
+    let mut args = MinesweeperArgs::builder()
+        .width(side)
+        .height(side)
+        .mines(mines)
+        .seed(1)
+        .build()
+        .unwrap();
+    args.auto_start = false;
+    Minesweeper::new(args)
+}
+
+fn main() {
+    // one flood opens almost the whole board, the time includes placing the mine
+    for side in [512, 1024, 2048, 4096] {
+        bench(
+            &format!("nearly empty {side}x{side}, first open"),
+            || board(side, 1),
+            |game| {
+                black_box(game.open(side / 2, side / 2));
+            },
+        );
+    }
+
+    // a small flood on a board with millions of closed cells left
+    for side in [1024, 2048, 4096] {
+        let mines = side as u32 * side as u32 / 5;
+        bench(
+            &format!("dense {side}x{side}, small flood"),
+            || {
+                let mut game = board(side, mines);
+                game.open(side / 2, side / 2);
+                let zero = game
+                    .game_state
+                    .cells
+                    .iter()
+                    .position(|c| matches!(c.visibility, Hidden(_)) && c.content == Empty(0));
+                (game, zero)
+            },
+            |(game, zero)| {
+                if let Some(i) = *zero {
+                    let w = game.args.width as usize;
+                    black_box(game.open((i % w) as u16, (i / w) as u16));
+                }
+            },
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use self::GameCommand::*;
-use crate::args::MinesweeperArgs;
+use crate::args::{FlagLimit, MinesweeperArgs};
 use crate::cell::Cell;
 use crate::cell_content::CellContent;
 use crate::cell_content::CellContent::Empty;
@@ -8,12 +8,12 @@ use crate::diff::Diff::{MultiCell, SingleCell};
 use crate::diff::*;
 use crate::flag::Flag::*;
 use crate::minesweeper::GameState;
+use crate::solver::{resolve_trivial, solve_all};
 use crate::tile_visibility::TileVisibility;
 use crate::tile_visibility::TileVisibility::{Hidden, Show};
 use crate::util::{DIRS_8, Sign, i_xy, valid_neighbors, xy_i};
 use crate::win_state::WinState::*;
 use CellContent::Mine;
-use std::collections::VecDeque;
 
 pub type Cursor = (u16, u16);
 #[derive(Copy, Clone, Debug)]
@@ -28,7 +28,14 @@ pub enum GameCommand {
     OpenCell(Cursor),
     FlagCell(Cursor),
     ClearFlag(Cursor),
+    /// Opens the unflagged neighbors of a number that has as many flags around it as its value
+    Chord(Cursor),
+    /// Flags the hidden neighbors of a number that has exactly as many of them as its value
+    FlagNeighbors(Cursor),
     Surrender,
+    AutoResolve,
+    /// Plays out the rest of the board when it can be solved from what is visible
+    Finish,
 }
 
 impl GameCommand {
@@ -44,9 +51,11 @@ impl GameCommand {
             OpenCell(xy) => xy_i(xy, w, h).and_then(|i| {
                 let cell = &mut cells[i];
 
-                let Hidden(Clear | FlaggedMaybe) = cell.visibility else {
-                    return None;
-                };
+                match cell.visibility {
+                    Hidden(Clear | FlaggedMaybe) => {}
+                    Hidden(Flagged) if args.open_flagged => {}
+                    _ => return None,
+                }
                 match cell.content {
                     Empty(0) => Some(MultiCell(expand_cell_diff_result(cells, w, h, i))),
                     Empty(_) => Some(cell.diff_result(i, Show)),
@@ -55,10 +64,26 @@ impl GameCommand {
             }),
             FlagCell(xy) => xy_i(xy, w, h).and_then(|i| {
                 let cell = &mut cells[i];
-                if let Hidden(flag) = cell.visibility {
-                    Some(cell.diff_result(i, Hidden(flag.next())))
+                let Hidden(flag) = cell.visibility else {
+                    return None;
+                };
+                let capped = args.flag_limit == FlagLimit::Cap && game.flagged_cells >= args.mines;
+                if capped && flag.next() == Flagged {
+                    return None;
+                }
+                let diff = cell.diff_result(i, Hidden(flag.next()));
+                if !args.auto_chord_on_flag || flag.next() != Flagged {
+                    return Some(diff);
+                }
+                // the numbers this flag completes are chorded in the same move
+                let mut chorded = vec![];
+                for xy in valid_neighbors(&DIRS_8, xy, w, h) {
+                    chorded.extend(chord_diffs(cells, w, h, xy_i(xy, w, h).unwrap()));
+                }
+                if chorded.is_empty() {
+                    Some(diff)
                 } else {
-                    None
+                    Some(diff.extended(chorded))
                 }
             }),
             ClearFlag(xy) => xy_i(xy, w, h).and_then(|i| {
@@ -69,6 +94,39 @@ impl GameCommand {
                     Some(cell.diff_result(i, Hidden(Clear)))
                 }
             }),
+            Chord(xy) => xy_i(xy, w, h).and_then(|i| {
+                let ret = chord_diffs(cells, w, h, i);
+                if ret.is_empty() {
+                    None
+                } else {
+                    Some(MultiCell(ret))
+                }
+            }),
+            FlagNeighbors(xy) => xy_i(xy, w, h).and_then(|i| {
+                let (Show, Empty(n @ 1..)) = (cells[i].visibility, cells[i].content) else {
+                    return None;
+                };
+                let hidden: Vec<usize> = valid_neighbors(&DIRS_8, xy, w, h)
+                    .map(|xy| xy_i(xy, w, h).unwrap())
+                    .filter(|&j| matches!(cells[j].visibility, Hidden(_)))
+                    .collect();
+                if hidden.len() != n as usize {
+                    return None;
+                }
+                let mut ret = vec![];
+                for j in hidden {
+                    if cells[j].visibility != Hidden(Flagged) {
+                        ret.push(cells[j].diff(j, Hidden(Flagged)));
+                    }
+                }
+                if ret.is_empty() {
+                    None
+                } else {
+                    Some(MultiCell(ret))
+                }
+            }),
+            // there is nothing to lose in practice, restarting is the way out
+            Surrender if game.practice => None,
             Surrender => {
                 let mut ret = vec![];
                 ret.reserve_exact(cells.len());
@@ -80,18 +138,54 @@ impl GameCommand {
                 }
                 Some(MultiCell(ret))
             }
+            AutoResolve => resolve_trivial(game, w, h),
+            Finish => solve_all(game, w, h, args.mines),
         }
     }
 }
-fn expand_cell_diff_result(cells: &mut [Cell], w: u16, h: u16, idx: usize) -> Vec<SingleCellDiff> {
+/// Opens the unflagged neighbors of the number at `idx` when it has as many flags around it as its value,
+/// nothing when it doesn't
+fn chord_diffs(cells: &mut [Cell], w: u16, h: u16, idx: usize) -> Vec<SingleCellDiff> {
+    let (Show, Empty(n @ 1..)) = (cells[idx].visibility, cells[idx].content) else {
+        return vec![];
+    };
+    let neighbors: Vec<usize> = valid_neighbors(&DIRS_8, i_xy(idx, w, h).unwrap(), w, h)
+        .map(|xy| xy_i(xy, w, h).unwrap())
+        .collect();
+    let flags = neighbors
+        .iter()
+        .filter(|&&j| cells[j].visibility == Hidden(Flagged))
+        .count();
+    if flags != n as usize {
+        return vec![];
+    }
+    let mut ret = vec![];
+    for j in neighbors {
+        match (cells[j].visibility, cells[j].content) {
+            (Hidden(Flagged) | Show, _) => continue,
+            (Hidden(_), Empty(0)) => ret.extend(expand_cell_diff_result(cells, w, h, j)),
+            (Hidden(_), _) => ret.push(cells[j].diff(j, Show)),
+        }
+    }
+    ret
+}
+
+/// Opens `idx` and floods through its zero neighbors.
+/// Cells are marked visited by turning `Show`, so no separate visited set is needed.
+/// The result grows as the flood does, reserving for every closed cell would cost the
+/// size of the board for the small floods that are the common case.
+pub fn expand_cell_diff_result(
+    cells: &mut [Cell],
+    w: u16,
+    h: u16,
+    idx: usize,
+) -> Vec<SingleCellDiff> {
     let mut ret = vec![];
 
-    let mut stack = VecDeque::<Cursor>::new();
+    let mut stack = vec![i_xy(idx, w, h).unwrap()];
     ret.push(cells[idx].diff(idx, Show));
 
-    stack.push_back(i_xy(idx, w, h).unwrap());
-
-    while let Some(c) = stack.pop_back() {
+    while let Some(c) = stack.pop() {
         for xy in valid_neighbors(&DIRS_8, c, w, h) {
             let Some(i) = xy_i(xy, w, h) else {
                 unreachable!()
@@ -104,10 +198,12 @@ fn expand_cell_diff_result(cells: &mut [Cell], w: u16, h: u16, idx: usize) -> Ve
             ret.push(cell.diff(i, Show));
 
             if n == 0 {
-                stack.push_back(xy);
+                stack.push(xy);
             }
         }
     }
+    // the diff is kept in the history, don't hold on to the unused part
+    ret.shrink_to_fit();
     ret
 }
 
@@ -133,6 +229,7 @@ pub enum RestartAction {
     ResizeV(Sign),
     IncrementMinesPercent(Sign),
     IncrementMines(Sign),
+    SetMines(u32),
 }
 
 #[derive(Copy, Clone, Debug)]
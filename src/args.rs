@@ -1,8 +1,98 @@
-use clap::Parser;
+use crate::theme::ThemeName;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
 
 /// Command line minesweeper
-#[derive(Parser, Copy, Clone, Default, Debug)]
+#[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
+pub struct Cli {
+    #[command(flatten)]
+    pub game: MinesweeperArgs,
+    #[command(subcommand)]
+    pub command: Option<CliCommand>,
+    /// let others watch this game by connecting to the address
+    #[arg(long, value_name = "ADDR", conflicts_with = "watch")]
+    pub serve: Option<String>,
+    /// watch the game served at the address
+    #[arg(long, value_name = "ADDR")]
+    pub watch: Option<String>,
+    /// size the board to the terminal, ignoring width and height
+    #[arg(long)]
+    pub fit: bool,
+    /// print the seed to stderr before starting and show it on new boards
+    #[arg(long)]
+    pub print_seed: bool,
+    /// show the seed in the title while playing
+    #[arg(long)]
+    pub show_seed: bool,
+    /// in practice or with extra lives, say when a flag turns out to be on an empty cell as it is opened
+    #[arg(long)]
+    pub warn_wrong_flags: bool,
+    /// cells the view moves per scroll wheel step
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+    pub scroll_step: Option<u16>,
+    /// ignore q and esc so only ctrl-c quits, for playing with the mouse
+    #[arg(long)]
+    pub mouse_only: bool,
+    /// start a new board this many milliseconds after a win or a loss, any key cancels it
+    #[arg(long, value_name = "DELAY_MS", num_args = 0..=1, default_missing_value = "1500")]
+    pub auto_restart: Option<u64>,
+    /// config file to read instead of the default one, also set by MINESWEEPER_CONFIG
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+    /// refuse out of range board settings instead of adjusting them
+    #[arg(long)]
+    pub strict: bool,
+    /// play the commands in a file without the terminal ui, then print the board
+    #[arg(long, value_name = "PATH")]
+    pub script: Option<PathBuf>,
+    /// record the session as an asciicast v2 file that asciinema can play back
+    #[arg(long, value_name = "PATH")]
+    pub cast: Option<PathBuf>,
+    /// print the board with colors when quitting
+    #[arg(long)]
+    pub print: bool,
+    /// save the board as an svg when quitting
+    #[arg(long, value_name = "PATH")]
+    pub export_svg: Option<PathBuf>,
+    /// save the board as a png when quitting
+    #[cfg(feature = "image")]
+    #[arg(long, value_name = "PATH")]
+    pub export_png: Option<PathBuf>,
+    /// `--mines` was given on the command line rather than left at its default
+    #[arg(skip)]
+    pub mines_given: bool,
+}
+
+impl Cli {
+    /// Parses the command line like [`Parser::parse`], also noting which defaults were overridden
+    pub fn parse_args() -> Self {
+        Self::from_matches(&Self::command().get_matches()).unwrap_or_else(|e| e.exit())
+    }
+
+    /// The [`Cli`] of already parsed `matches`, see [`Self::parse_args`]
+    pub fn from_matches(matches: &ArgMatches) -> Result<Self, clap::Error> {
+        let mut cli = Self::from_arg_matches(matches)?;
+        cli.mines_given = matches.value_source("mines") == Some(ValueSource::CommandLine);
+        Ok(cli)
+    }
+}
+
+#[derive(Subcommand, Copy, Clone, Debug)]
+pub enum CliCommand {
+    /// show or manage the persisted statistics
+    Stats {
+        /// delete the persisted statistics
+        #[arg(long)]
+        reset: bool,
+        /// don't ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+#[derive(Args, Copy, Clone, Default, Debug)]
 pub struct MinesweeperArgs {
     /// width
     #[arg(short = 'x', long, default_value_t = 32)]
@@ -11,16 +101,473 @@ pub struct MinesweeperArgs {
     #[arg(short = 'y', long, default_value_t = 16)]
     pub height: u16,
     /// amount of mines
-    #[arg(short, long, default_value_t = 100)]
+    #[arg(short, long, default_value_t = 100, conflicts_with = "mines_percent")]
     pub mines: u32,
+    /// amount of mines as a percentage of the cells
+    #[arg(long, alias = "density", value_name = "PERCENT")]
+    pub mines_percent: Option<f32>,
+    /// cells around the first click guaranteed to be mine free (0 = just the clicked cell, 1 = 3x3, 2 = 5x5)
+    #[arg(long, default_value_t = 1)]
+    pub safe_radius: u8,
+    /// seed for the mine layout, random if not given
+    #[arg(long)]
+    pub seed: Option<u64>,
+    /// what happens when there are as many flags as mines
+    #[arg(long, value_enum, default_value_t)]
+    pub flag_limit: FlagLimit,
+    /// opening a flagged cell removes the flag and opens it instead of doing nothing
+    #[arg(long)]
+    pub open_flagged: bool,
+    /// win by flagging every mine instead of opening every empty cell
+    #[arg(long)]
+    pub win_on_flags: bool,
+    /// with --win-on-flags, flag the remaining cells once only mines are left hidden, winning the game
+    #[arg(long)]
+    pub auto_complete: bool,
+    /// a flag that completes an open number also opens the rest of its neighbors, in the same move
+    #[arg(long)]
+    pub auto_chord_on_flag: bool,
+    /// a board without mines, for demos and trying out themes
+    #[arg(long, conflicts_with_all = ["mines", "mines_percent"])]
+    pub zen: bool,
+    /// mines that can be opened before the game is lost
+    #[arg(long, default_value_t = 1)]
+    pub lives: u32,
+    /// opening a mine counts as a mistake instead of ending the game
+    #[arg(long)]
+    pub practice: bool,
+    /// open a random empty region right away instead of waiting for the first click
+    #[arg(long, conflicts_with = "reveal_all")]
+    pub auto_start: bool,
+    /// start with this percentage of the empty cells open, picked from the seed (implies --auto-start)
+    #[arg(long, value_name = "PERCENT", default_value_t = 0, conflicts_with = "reveal_all", value_parser = clap::value_parser!(u8).range(0..100))]
+    pub reveal: u8,
+    /// symmetric boards that can be solved from the start without guessing (implies --auto-start)
+    #[arg(long, conflicts_with = "reveal_all")]
+    pub puzzle: bool,
+    /// resizing one side of the board scales the other to keep its proportions
+    #[arg(long)]
+    pub lock_aspect: bool,
+    /// resizing the board scales the mines with it to keep the density
+    #[arg(long)]
+    pub keep_density: bool,
+    /// smallest width and height boards are clamped and resized to
+    #[arg(long, value_name = "N", default_value_t = MIN_SIZE, value_parser = clap::value_parser!(u16).range(1..=MAX_SIZE as i64))]
+    pub min_size: u16,
+    /// largest width and height boards are clamped and resized to
+    #[arg(long, value_name = "N", default_value_t = MAX_SIZE, value_parser = clap::value_parser!(u16).range(1..=MAX_SIZE as i64))]
+    pub max_size: u16,
+    /// seconds added to the time for every undo, redoing doesn't take them back
+    #[arg(long, value_name = "SECONDS", default_value_t = 0)]
+    pub undo_penalty: u32,
+    /// record when each cell was opened, for analysing a game afterwards
+    #[arg(long)]
+    pub open_times: bool,
+    /// start with the whole board revealed, for screenshots
+    #[arg(long)]
+    pub reveal_all: bool,
+    /// colors of the board
+    #[arg(long, value_enum, default_value_t)]
+    pub theme: ThemeName,
+    /// terminal cursor shape
+    #[arg(long, value_enum, default_value_t)]
+    pub cursor: CursorShape,
+    /// only show the terminal cursor, without highlighting the cell under it
+    #[arg(long)]
+    pub no_cursor_highlight: bool,
+}
+
+#[derive(ValueEnum, Copy, Clone, Default, Debug, PartialEq)]
+pub enum FlagLimit {
+    /// keep flagging, the counter turns red past the mine count
+    #[default]
+    Warn,
+    /// no more flags can be placed
+    Cap,
+}
+
+#[derive(ValueEnum, Copy, Clone, Default, Debug)]
+pub enum CursorShape {
+    /// leave the terminal's cursor as is
+    #[default]
+    Default,
+    /// steady block
+    #[value(alias = "block")]
+    Solid,
+    /// blinking block
+    Blink,
+    /// steady bar
+    Bar,
+    /// steady underscore
+    Underscore,
+}
+
+/// default of `--min-size`
+pub const MIN_SIZE: u16 = 8;
+// the viewport math in ui works in i16 and width * height must fit in a u32
+pub const MAX_SIZE: u16 = 4096;
+pub const MAX_SAFE_RADIUS: u8 = 2;
+/// largest `--puzzle` board, 200x200, a single try at solving a bigger one can take seconds
+pub const PUZZLE_MAX_CELLS: u32 = 40_000;
+/// roughly the density of the default 32x16 board with 100 mines
+pub const DEFAULT_DENSITY: f32 = 20.0;
+
+/// density of the mine count suggested for a new board, between easy and hard
+pub const SUGGESTED_DENSITY: f32 = 17.0;
+
+/// A balanced mine count for a `width` x `height` board
+pub fn suggested_mines(width: u16, height: u16) -> u32 {
+    percent_of(width as u32 * height as u32, SUGGESTED_DENSITY).max(1)
+}
+
+/// `percent` of `size`, rounded, with the percentage clamped to [0, 100]
+///
+/// ```
+/// use minesweeper::args::percent_of;
+///
+/// assert_eq!(percent_of(512, 0.0), 0);
+/// assert_eq!(percent_of(512, 100.0), 512);
+/// assert_eq!(percent_of(512, 20.0), 102);
+/// assert_eq!(percent_of(512, -5.0), 0);
+/// assert_eq!(percent_of(512, 150.0), 512);
+/// ```
+pub fn percent_of(size: u32, percent: f32) -> u32 {
+    (size as f64 * percent.clamp(0.0, 100.0) as f64 / 100.0).round() as u32
 }
 
 impl MinesweeperArgs {
+    /// Checks the arguments are playable as given, where [`Self::clamped`] would adjust them
+    ///
+    /// With a safe radius of 1 or more the first click opens the whole safe square, so the densest
+    /// board that passes leaves just that square empty instead of a first click that opens a single number.
+    ///
+    /// ```
+    /// use minesweeper::args::{ArgsError, MinesweeperArgs};
+    /// use minesweeper::minesweeper::Minesweeper;
+    /// use minesweeper::win_state::WinState;
+    ///
+    /// // 8x8 with the default 3x3 safe square
+    /// let mut args = MinesweeperArgs::builder().width(8).height(8).seed(1).build().unwrap();
+    /// args.mines = 56;
+    /// let err = args.validate().unwrap_err();
+    /// assert_eq!(err, ArgsError::TooManyMines { mines: 56, max: 55, safe_radius: 1 });
+    ///
+    /// // at the limit the first click opens the whole square, even in a corner, and that's the game
+    /// args.mines = 55;
+    /// assert!(args.validate().is_ok());
+    /// let mut game = Minesweeper::new(args);
+    /// game.open(0, 0);
+    /// assert!(matches!(game.game_state.win_state, WinState::Won));
+    /// ```
+    pub fn validate(&self) -> Result<(), ArgsError> {
+        let (width, height) = (self.width, self.height);
+        let clamped = self.clamped();
+        let (min, max) = (clamped.min_size, clamped.max_size);
+        if width < min || height < min {
+            return Err(ArgsError::TooSmall { width, height, min });
+        }
+        if width > max || height > max {
+            return Err(ArgsError::TooLarge { width, height, max });
+        }
+        if self.puzzle && width as u32 * height as u32 > PUZZLE_MAX_CELLS {
+            return Err(ArgsError::PuzzleTooLarge { width, height });
+        }
+        if self.safe_radius > MAX_SAFE_RADIUS {
+            return Err(ArgsError::SafeRadiusTooLarge(self.safe_radius));
+        }
+        if self.safe_radius > clamped.safe_radius {
+            return Err(ArgsError::SafeRadiusCoversBoard(self.safe_radius));
+        }
+        if let Some(percent) = self.mines_percent
+            && !(0.0..=100.0).contains(&percent)
+        {
+            return Err(ArgsError::InvalidDensity(percent));
+        }
+        let mines = match self.mines_percent {
+            Some(percent) => percent_of(width as u32 * height as u32, percent),
+            None if self.zen => 0,
+            None => self.mines,
+        };
+        if mines == 0 && !self.zen {
+            return Err(ArgsError::NoMines);
+        }
+        // an odd count that would otherwise fit is only rounded down to pair the mines up
+        let unpaired = Self {
+            puzzle: false,
+            ..*self
+        };
+        if self.puzzle && mines > 1 && mines % 2 == 1 && mines <= unpaired.clamped().mines {
+            return Err(ArgsError::OddPuzzleMines(mines));
+        }
+        if mines > clamped.mines && !self.zen {
+            let max = clamped.mines;
+            let safe_radius = clamped.safe_radius;
+            return Err(ArgsError::TooManyMines {
+                mines,
+                max,
+                safe_radius,
+            });
+        }
+        if self.lives == 0 {
+            return Err(ArgsError::NoLives);
+        }
+        if self.lives > clamped.lives {
+            return Err(ArgsError::TooManyLives {
+                lives: self.lives,
+                mines,
+            });
+        }
+        Ok(())
+    }
+
     pub fn clamped(mut self) -> Self {
-        self.width = self.width.clamp(8, 256);
-        self.height = self.height.clamp(8, 256);
-        let max_mines = self.width as u32 * self.height as u32 - 9;
-        self.mines = self.mines.clamp(1, max_mines);
+        self.min_size = self.min_size.clamp(1, MAX_SIZE);
+        self.max_size = self.max_size.clamp(self.min_size, MAX_SIZE);
+        self.width = self.width.clamp(self.min_size, self.max_size);
+        self.height = self.height.clamp(self.min_size, self.max_size);
+        self.safe_radius = self.safe_radius.min(MAX_SAFE_RADIUS);
+        if self.puzzle {
+            // the first click decides the layout, so it comes from the seed too
+            self.auto_start = true;
+        }
+        if self.reveal > 0 {
+            // the head start grows from the auto start's region
+            self.auto_start = true;
+        }
+        if self.auto_start {
+            // the start cell has to be a zero, so its neighbors are mine free too
+            self.safe_radius = self.safe_radius.max(1);
+        }
+        let size = self.width as u32 * self.height as u32;
+        // on tiny boards a safe square covering everything would leave no room for a mine
+        while self.safe_radius > 0 && (2 * self.safe_radius as u32 + 1).pow(2) >= size {
+            self.safe_radius -= 1;
+        }
+        let safe_side = 2 * self.safe_radius as u32 + 1;
+        if let Some(percent) = self.mines_percent.take() {
+            self.mines = percent_of(size, percent);
+        }
+        let max_mines = size.saturating_sub(safe_side * safe_side);
+        self.mines = if self.zen {
+            0
+        } else {
+            // a single cell board has nothing but the safe cell, it gets no mine
+            self.mines.max(1).min(max_mines)
+        };
+        if self.puzzle && self.mines > 1 {
+            // the mines come in mirrored pairs
+            self.mines -= self.mines % 2;
+        }
+        // more lives than mines would make surrendering a win
+        self.lives = self.lives.clamp(1, self.mines.max(1));
         self
     }
 }
+
+/// Classic board presets
+#[derive(ValueEnum, Copy, Clone, Debug)]
+pub enum Difficulty {
+    /// 9x9, 10 mines
+    Beginner,
+    /// 16x16, 40 mines
+    Intermediate,
+    /// 30x16, 99 mines
+    Expert,
+}
+
+impl Difficulty {
+    /// Width, height and mines of the preset
+    pub fn board(self) -> (u16, u16, u32) {
+        match self {
+            Difficulty::Beginner => (9, 9, 10),
+            Difficulty::Intermediate => (16, 16, 40),
+            Difficulty::Expert => (30, 16, 99),
+        }
+    }
+}
+
+/// Why a [`MinesweeperArgs`] is not playable as given
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ArgsError {
+    /// both an amount of mines and a density were given
+    MinesAndDensity,
+    TooSmall {
+        width: u16,
+        height: u16,
+        min: u16,
+    },
+    TooLarge {
+        width: u16,
+        height: u16,
+        max: u16,
+    },
+    /// `--puzzle` on a board above [`PUZZLE_MAX_CELLS`]
+    PuzzleTooLarge {
+        width: u16,
+        height: u16,
+    },
+    SafeRadiusTooLarge(u8),
+    /// the first click's safe square would leave no room for a mine
+    SafeRadiusCoversBoard(u8),
+    InvalidDensity(f32),
+    NoMines,
+    /// more mines than fit outside the first click's safe square
+    TooManyMines {
+        mines: u32,
+        max: u32,
+        safe_radius: u8,
+    },
+    /// `--puzzle` mirrors every mine, so the count has to be even
+    OddPuzzleMines(u32),
+    NoLives,
+    TooManyLives {
+        lives: u32,
+        mines: u32,
+    },
+}
+
+impl std::fmt::Display for ArgsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArgsError::MinesAndDensity => write!(f, "mines and mines percent are exclusive"),
+            ArgsError::TooSmall { width, height, min } => {
+                write!(f, "{width}x{height} is smaller than {min}x{min}")
+            }
+            ArgsError::TooLarge { width, height, max } => {
+                write!(f, "{width}x{height} is larger than {max}x{max}")
+            }
+            ArgsError::PuzzleTooLarge { width, height } => {
+                write!(
+                    f,
+                    "{width}x{height} is too large for --puzzle, which takes up to {PUZZLE_MAX_CELLS} cells"
+                )
+            }
+            ArgsError::SafeRadiusTooLarge(radius) => {
+                write!(f, "safe radius {radius} is above {MAX_SAFE_RADIUS}")
+            }
+            ArgsError::SafeRadiusCoversBoard(radius) => {
+                write!(
+                    f,
+                    "safe radius {radius} leaves no room for mines on this board"
+                )
+            }
+            ArgsError::InvalidDensity(percent) => {
+                write!(f, "{percent}% is not a percentage between 0 and 100")
+            }
+            ArgsError::NoMines => write!(f, "a board needs at least one mine, see --zen"),
+            ArgsError::TooManyMines {
+                mines,
+                max,
+                safe_radius: 0,
+            } => {
+                write!(f, "{mines} mines don't fit, this board takes at most {max}")
+            }
+            ArgsError::TooManyMines {
+                mines,
+                max,
+                safe_radius,
+            } => {
+                let side = 2 * safe_radius + 1;
+                write!(
+                    f,
+                    "{mines} mines don't fit, this board takes at most {max} to keep the {side}x{side} square around the first click free"
+                )
+            }
+            ArgsError::OddPuzzleMines(mines) => {
+                write!(f, "--puzzle pairs the mines up, {mines} is an odd count")
+            }
+            ArgsError::NoLives => write!(f, "a game needs at least one life"),
+            ArgsError::TooManyLives { lives, mines } => {
+                write!(f, "{lives} lives are more than the {mines} mines")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ArgsError {}
+
+/// Builds [`MinesweeperArgs`] starting from the command line defaults
+///
+/// ```
+/// use minesweeper::args::{Difficulty, MinesweeperArgs};
+/// use minesweeper::minesweeper::Minesweeper;
+///
+/// let args = MinesweeperArgs::builder()
+///     .difficulty(Difficulty::Intermediate)
+///     .width(20)
+///     .seed(42)
+///     .build()
+///     .unwrap();
+/// assert_eq!((args.width, args.height, args.mines), (20, 16, 40));
+///
+/// let mut game = Minesweeper::new(args);
+/// assert!(game.open(10, 8).is_some());
+///
+/// // only one way to pick the amount of mines
+/// assert!(MinesweeperArgs::builder().mines(10).mines_percent(15.0).build().is_err());
+/// ```
+#[derive(Clone, Debug)]
+pub struct MinesweeperArgsBuilder {
+    args: MinesweeperArgs,
+    mines: Option<u32>,
+}
+
+impl MinesweeperArgs {
+    pub fn builder() -> MinesweeperArgsBuilder {
+        let command = Self::augment_args(clap::Command::new("minesweeper"));
+        let matches = command.get_matches_from(["minesweeper"]);
+        MinesweeperArgsBuilder {
+            args: Self::from_arg_matches(&matches).unwrap(),
+            mines: None,
+        }
+    }
+}
+
+impl MinesweeperArgsBuilder {
+    pub fn width(mut self, width: u16) -> Self {
+        self.args.width = width;
+        self
+    }
+
+    pub fn height(mut self, height: u16) -> Self {
+        self.args.height = height;
+        self
+    }
+
+    pub fn mines(mut self, mines: u32) -> Self {
+        self.mines = Some(mines);
+        self
+    }
+
+    pub fn mines_percent(mut self, percent: f32) -> Self {
+        self.args.mines_percent = Some(percent);
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.args.seed = Some(seed);
+        self
+    }
+
+    /// Sets the size and mines of a preset, later setters override them
+    pub fn difficulty(mut self, difficulty: Difficulty) -> Self {
+        let (width, height, mines) = difficulty.board();
+        self.args.width = width;
+        self.args.height = height;
+        self.args.mines = mines;
+        self.args.mines_percent = None;
+        self
+    }
+
+    /// The arguments, clamped like [`MinesweeperArgs::clamped`]
+    pub fn build(self) -> Result<MinesweeperArgs, ArgsError> {
+        let mut args = self.args;
+        if let Some(mines) = self.mines {
+            if args.mines_percent.is_some() {
+                return Err(ArgsError::MinesAndDensity);
+            }
+            args.mines = mines;
+        }
+        Ok(args.clamped())
+    }
+}
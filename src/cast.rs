@@ -0,0 +1,66 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Passes the terminal output through while recording it as an
+/// [asciicast v2](https://docs.asciinema.org/manual/asciicast/v2/) file.
+/// Everything written between two flushes, a whole frame, becomes one event.
+pub struct CastWriter<W: Write> {
+    out: W,
+    file: BufWriter<File>,
+    start: Instant,
+    frame: Vec<u8>,
+}
+
+impl<W: Write> CastWriter<W> {
+    /// Creates the file and writes the header for a terminal of `columns` x `rows`
+    pub fn create(out: W, path: &Path, (columns, rows): (u16, u16)) -> io::Result<Self> {
+        let mut file = BufWriter::new(File::create(path)?);
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+        let header = serde_json::json!({
+            "version": 2,
+            "width": columns,
+            "height": rows,
+            "timestamp": timestamp,
+        });
+        writeln!(file, "{header}")?;
+        Ok(Self {
+            out,
+            file,
+            start: Instant::now(),
+            frame: vec![],
+        })
+    }
+}
+
+impl<W: Write> Write for CastWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.out.write(buf)?;
+        self.frame.extend_from_slice(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()?;
+        if self.frame.is_empty() {
+            return Ok(());
+        }
+        let time = self.start.elapsed().as_secs_f64();
+        let text = String::from_utf8_lossy(&self.frame);
+        serde_json::to_writer(&mut self.file, &(time, "o", text))?;
+        writeln!(self.file)?;
+        self.frame.clear();
+        Ok(())
+    }
+}
+
+impl<W: Write> Drop for CastWriter<W> {
+    fn drop(&mut self) {
+        // a frame cut short by quitting is still worth keeping
+        let _ = self.flush();
+        let _ = self.file.flush();
+    }
+}
@@ -3,10 +3,11 @@ use crate::cell_content::CellContent::*;
 use crate::flag::Flag::*;
 use crate::tile_visibility::TileVisibility;
 use crate::tile_visibility::TileVisibility::*;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::fmt::{Display, Formatter, Write};
 
-#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct Cell {
     pub visibility: TileVisibility,
     pub content: CellContent,
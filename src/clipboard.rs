@@ -0,0 +1,43 @@
+use std::fmt;
+
+/// The system clipboard, opened on first use and kept open so what was copied stays available
+#[derive(Default)]
+pub struct Clipboard {
+    #[cfg(feature = "clipboard")]
+    inner: Option<arboard::Clipboard>,
+}
+
+impl fmt::Debug for Clipboard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Clipboard").finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "clipboard")]
+impl Clipboard {
+    fn open(&mut self) -> Result<&mut arboard::Clipboard, String> {
+        if self.inner.is_none() {
+            self.inner = Some(arboard::Clipboard::new().map_err(|e| e.to_string())?);
+        }
+        Ok(self.inner.as_mut().unwrap())
+    }
+
+    pub fn set_text(&mut self, text: String) -> Result<(), String> {
+        self.open()?.set_text(text).map_err(|e| e.to_string())
+    }
+
+    pub fn get_text(&mut self) -> Result<String, String> {
+        self.open()?.get_text().map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(not(feature = "clipboard"))]
+impl Clipboard {
+    pub fn set_text(&mut self, _text: String) -> Result<(), String> {
+        Err("built without clipboard support".to_string())
+    }
+
+    pub fn get_text(&mut self) -> Result<String, String> {
+        Err("built without clipboard support".to_string())
+    }
+}
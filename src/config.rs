@@ -0,0 +1,361 @@
+use crate::args::{CursorShape, MinesweeperArgs};
+use crate::paths;
+use crate::theme::ThemeName;
+use clap::ValueEnum;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Environment variable pointing at the config file, `--config` takes precedence over it
+pub const CONFIG_ENV: &str = "MINESWEEPER_CONFIG";
+
+/// Preferences read from the config file, `key = value` per line, `#` starts a comment
+///
+/// ```text
+/// theme = dark
+/// cursor = bar
+/// scroll_step = 3
+/// time_thresholds = 60, 180
+/// undo = ctrl+z, ctrl+left
+/// redo = ctrl+y, ctrl+right
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Config {
+    pub theme: Option<ThemeName>,
+    pub cursor: Option<CursorShape>,
+    pub scroll_step: Option<u16>,
+    /// seconds after which the timer turns yellow, then red
+    pub time_thresholds: Option<(u64, u64)>,
+    pub keymap: Keymap,
+}
+
+/// A key and the modifiers held with it, written like `ctrl+shift+left` or `u`
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct KeyBinding {
+    pub modifiers: KeyModifiers,
+    pub code: KeyCode,
+}
+
+impl KeyBinding {
+    pub const fn new(modifiers: KeyModifiers, code: KeyCode) -> Self {
+        Self { modifiers, code }
+    }
+
+    /// Letters match regardless of case and shift, so `ctrl+z` also takes `ctrl+Z`
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        match (self.code, key.code) {
+            (KeyCode::Char(a), KeyCode::Char(b)) => {
+                a.eq_ignore_ascii_case(&b)
+                    && self.modifiers - KeyModifiers::SHIFT == key.modifiers - KeyModifiers::SHIFT
+            }
+            (a, b) => a == b && self.modifiers == key.modifiers,
+        }
+    }
+
+    /// Would this binding take a key press away from ctrl+c or one of the [`BUILTIN_KEYS`]?
+    pub fn is_reserved(&self) -> bool {
+        let quit = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        self.matches(&quit)
+            || BUILTIN_KEYS.iter().any(|&code| {
+                [KeyModifiers::NONE, KeyModifiers::SHIFT]
+                    .into_iter()
+                    .any(|modifiers| self.matches(&KeyEvent::new(code, modifiers)))
+            })
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "ctrl+"),
+            (KeyModifiers::ALT, "alt+"),
+            (KeyModifiers::SHIFT, "shift+"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                f.write_str(name)?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            code => write!(f, "{}", format!("{code:?}").to_ascii_lowercase()),
+        }
+    }
+}
+
+impl FromStr for KeyBinding {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        let mut parts: Vec<&str> = s.split('+').map(str::trim).collect();
+        let key = parts.pop().ok_or(())?.to_ascii_lowercase();
+        let mut modifiers = KeyModifiers::NONE;
+        for part in parts {
+            modifiers |= match part.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "shift" => KeyModifiers::SHIFT,
+                "alt" => KeyModifiers::ALT,
+                _ => return Err(()),
+            };
+        }
+        let code = match key.as_str() {
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "esc" => KeyCode::Esc,
+            "enter" => KeyCode::Enter,
+            "tab" => KeyCode::Tab,
+            "backspace" => KeyCode::Backspace,
+            "delete" => KeyCode::Delete,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "space" => KeyCode::Char(' '),
+            _ => {
+                let mut chars = key.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => return Err(()),
+                }
+            }
+        };
+        Ok(Self::new(modifiers, code))
+    }
+}
+
+/// Keys bound to the remappable actions, checked before the built in keys
+#[derive(Clone, Debug)]
+pub struct Keymap {
+    pub undo: Vec<KeyBinding>,
+    pub redo: Vec<KeyBinding>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            undo: vec![
+                KeyBinding::new(KeyModifiers::CONTROL, KeyCode::Char('z')),
+                KeyBinding::new(KeyModifiers::CONTROL, KeyCode::Left),
+            ],
+            redo: vec![
+                KeyBinding::new(KeyModifiers::CONTROL, KeyCode::Char('y')),
+                KeyBinding::new(KeyModifiers::CONTROL, KeyCode::Right),
+            ],
+        }
+    }
+}
+
+/// Keys the game handles itself, pressed alone or with shift. ctrl and alt are left to the keymap,
+/// except for ctrl+c which always quits.
+pub const BUILTIN_KEYS: &[KeyCode] = &[
+    KeyCode::Esc,
+    KeyCode::PageUp,
+    KeyCode::PageDown,
+    KeyCode::Backspace,
+    KeyCode::Left,
+    KeyCode::Right,
+    KeyCode::Up,
+    KeyCode::Down,
+    KeyCode::Char(' '),
+    KeyCode::Char('q'),
+    KeyCode::Char('k'),
+    KeyCode::Char('a'),
+    KeyCode::Char('A'),
+    KeyCode::Char('v'),
+    KeyCode::Char('c'),
+    KeyCode::Char('l'),
+    KeyCode::Char('L'),
+    KeyCode::Char('h'),
+    KeyCode::Char('m'),
+    KeyCode::Char('M'),
+    KeyCode::Char('@'),
+    KeyCode::Char('D'),
+    KeyCode::Char('y'),
+    KeyCode::Char('Y'),
+    KeyCode::Char('s'),
+    KeyCode::Char('g'),
+    KeyCode::Char('?'),
+    KeyCode::Char('i'),
+    KeyCode::Char('r'),
+    KeyCode::Char('n'),
+    KeyCode::Char('p'),
+    KeyCode::Char('x'),
+    KeyCode::Char('F'),
+    KeyCode::Char('z'),
+    KeyCode::Char('f'),
+    KeyCode::Char('b'),
+    KeyCode::Char('+'),
+    KeyCode::Char('-'),
+];
+
+pub fn default_config_path() -> Option<PathBuf> {
+    Some(paths::config_dir()?.join("config"))
+}
+
+/// What is wrong with a config line
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConfigErrorKind {
+    /// not a `key = value` line
+    Syntax,
+    UnknownKey(String),
+    InvalidValue {
+        key: String,
+        value: String,
+    },
+    DuplicateKey(String),
+    /// the same key is bound to two actions
+    DuplicateBinding(String),
+    /// the key is ctrl+c or one the game already uses
+    ReservedBinding(String),
+}
+
+/// A config problem and the 1 based line it is on
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfigError {
+    pub line: usize,
+    pub kind: ConfigErrorKind,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            ConfigErrorKind::Syntax => write!(f, "expected `key = value`"),
+            ConfigErrorKind::UnknownKey(key) => write!(f, "unknown key `{key}`"),
+            ConfigErrorKind::InvalidValue { key, value } => {
+                write!(f, "invalid {key} `{value}`")
+            }
+            ConfigErrorKind::DuplicateKey(key) => write!(f, "`{key}` is set twice"),
+            ConfigErrorKind::DuplicateBinding(binding) => {
+                write!(f, "`{binding}` is bound to more than one action")
+            }
+            ConfigErrorKind::ReservedBinding(binding) => {
+                write!(f, "`{binding}` is already used by the game")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    /// Reads the file given on the command line, else the one in [`CONFIG_ENV`], else the default one.
+    /// Only the default file may be missing. An invalid file is reported on stderr and the defaults are used.
+    pub fn load(cli_path: Option<&Path>) -> io::Result<Self> {
+        let explicit = cli_path
+            .map(Path::to_path_buf)
+            .or_else(|| std::env::var_os(CONFIG_ENV).map(PathBuf::from));
+        let (path, text) = match explicit {
+            Some(path) => match fs::read_to_string(&path) {
+                Ok(text) => (path, text),
+                Err(e) => {
+                    let message = format!("config {}: {e}", path.display());
+                    return Err(io::Error::new(e.kind(), message));
+                }
+            },
+            None => {
+                let Some(path) = default_config_path() else {
+                    return Ok(Self::default());
+                };
+                match fs::read_to_string(&path) {
+                    Ok(text) => (path, text),
+                    Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+                    Err(e) => return Err(e),
+                }
+            }
+        };
+        Ok(Self::parse(&text).unwrap_or_else(|e| {
+            eprintln!("warning: {} {e}, using the default config", path.display());
+            Self::default()
+        }))
+    }
+
+    pub fn parse(text: &str) -> Result<Self, ConfigError> {
+        let mut config = Self::default();
+        let mut seen = BTreeSet::new();
+        // the last line touching the keymap, where a clash gets reported
+        let mut keymap_line = 0;
+        for (n, line) in text.lines().enumerate() {
+            let error = |kind| ConfigError { line: n + 1, kind };
+            let line = line.split('#').next().unwrap_or_default();
+            if line.trim().is_empty() {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(error(ConfigErrorKind::Syntax));
+            };
+            let (key, value) = (key.trim(), value.trim());
+            let invalid = || {
+                error(ConfigErrorKind::InvalidValue {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                })
+            };
+            match key {
+                "theme" => {
+                    config.theme = Some(ThemeName::from_str(value, true).map_err(|_| invalid())?)
+                }
+                "cursor" => {
+                    config.cursor = Some(CursorShape::from_str(value, true).map_err(|_| invalid())?)
+                }
+                "scroll_step" => {
+                    let step = value.parse().ok().filter(|&step| step >= 1);
+                    config.scroll_step = Some(step.ok_or_else(invalid)?);
+                }
+                "time_thresholds" => {
+                    let thresholds = value
+                        .split_once(',')
+                        .and_then(|(warn, late)| {
+                            Some((warn.trim().parse().ok()?, late.trim().parse().ok()?))
+                        })
+                        .filter(|(warn, late)| warn <= late);
+                    config.time_thresholds = Some(thresholds.ok_or_else(invalid)?);
+                }
+                "undo" | "redo" => {
+                    let bindings = value
+                        .split(',')
+                        .map(|binding| binding.parse().map_err(|_| invalid()))
+                        .collect::<Result<Vec<KeyBinding>, _>>()?;
+                    if let Some(binding) = bindings.iter().find(|binding| binding.is_reserved()) {
+                        return Err(error(ConfigErrorKind::ReservedBinding(binding.to_string())));
+                    }
+                    if key == "undo" {
+                        config.keymap.undo = bindings;
+                    } else {
+                        config.keymap.redo = bindings;
+                    }
+                    keymap_line = n + 1;
+                }
+                _ => return Err(error(ConfigErrorKind::UnknownKey(key.to_string()))),
+            }
+            if !seen.insert(key) {
+                return Err(error(ConfigErrorKind::DuplicateKey(key.to_string())));
+            }
+        }
+        let Keymap { undo, redo } = &config.keymap;
+        if let Some(binding) = undo.iter().find(|binding| redo.contains(binding)) {
+            return Err(ConfigError {
+                line: keymap_line,
+                kind: ConfigErrorKind::DuplicateBinding(binding.to_string()),
+            });
+        }
+        Ok(config)
+    }
+
+    /// Fills in what the command line left at its default
+    pub fn apply(&self, args: &mut MinesweeperArgs) {
+        if let (Some(theme), ThemeName::Default) = (self.theme, args.theme) {
+            args.theme = theme;
+        }
+        if let (Some(cursor), CursorShape::Default) = (self.cursor, args.cursor) {
+            args.cursor = cursor;
+        }
+    }
+}
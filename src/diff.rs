@@ -1,11 +1,53 @@
 use crate::cell::Cell;
-#[derive(Debug)]
+use crate::cell_content::CellContent::Empty;
+use crate::flag::Flag::Flagged;
+use crate::tile_visibility::TileVisibility::{Hidden, Show};
+use serde::{Deserialize, Serialize};
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum Diff {
     SingleCell(SingleCellDiff),
     MultiCell(Vec<SingleCellDiff>),
 }
 
-#[derive(Debug, Default, Copy, Clone)]
+impl Diff {
+    /// Index of every cell the diff touches
+    pub fn indices(&self) -> Vec<usize> {
+        match self {
+            Diff::SingleCell(diff) => vec![diff.index],
+            Diff::MultiCell(diffs) => diffs.iter().map(|diff| diff.index).collect(),
+        }
+    }
+
+    /// Index of every flag on an empty cell the diff opens, by a flood, the solver or a reveal
+    pub fn opened_wrong_flags(&self) -> Vec<usize> {
+        let wrong = |diff: &&SingleCellDiff| {
+            matches!(
+                (
+                    diff.before.visibility,
+                    diff.before.content,
+                    diff.after.visibility
+                ),
+                (Hidden(Flagged), Empty(_), Show)
+            )
+        };
+        match self {
+            Diff::SingleCell(diff) => [diff].into_iter().filter(wrong).map(|d| d.index).collect(),
+            Diff::MultiCell(diffs) => diffs.iter().filter(wrong).map(|d| d.index).collect(),
+        }
+    }
+
+    /// This diff followed by `more`, as a single move
+    pub fn extended(self, more: Vec<SingleCellDiff>) -> Diff {
+        let mut diffs = match self {
+            Diff::SingleCell(diff) => vec![diff],
+            Diff::MultiCell(diffs) => diffs,
+        };
+        diffs.extend(more);
+        Diff::MultiCell(diffs)
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone, Serialize, Deserialize)]
 pub struct SingleCellDiff {
     pub index: usize,
     pub before: Cell,
@@ -0,0 +1,140 @@
+use crate::args::MinesweeperArgs;
+use crate::minesweeper::GameState;
+use crate::theme::{Theme, rgb};
+use ratatui::crossterm::style::{Attribute, ContentStyle};
+use ratatui::style::Modifier;
+use std::fmt::Write;
+#[cfg(feature = "image")]
+use std::fs::File;
+use std::io;
+#[cfg(feature = "image")]
+use std::io::BufWriter;
+use std::path::Path;
+
+const CELL_PX: usize = 12;
+#[cfg(feature = "image")]
+const GLYPH_SCALE: usize = 2;
+
+/// Builds an svg of the board with a `<rect>` per cell and a `<text>` for every glyph but hidden ones
+pub fn svg(game: &GameState, args: &MinesweeperArgs) -> String {
+    let w = args.width as usize;
+    let h = args.height as usize;
+    let hex = |(r, g, b): (u8, u8, u8)| format!("#{r:02x}{g:02x}{b:02x}");
+    let theme = Theme::from(args.theme);
+
+    let mut ret = String::new();
+    let _ = writeln!(
+        ret,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" font-family="monospace" font-size="{}" font-weight="bold" text-anchor="middle" dominant-baseline="central">"#,
+        w * CELL_PX,
+        h * CELL_PX,
+        CELL_PX - 2,
+    );
+    for (i, cell) in game.cells.iter().enumerate() {
+        let (c, fg, bg, _) = theme.tile_style(cell);
+        let (x, y) = (i % w * CELL_PX, i / w * CELL_PX);
+        let _ = writeln!(
+            ret,
+            r#"<rect x="{x}" y="{y}" width="{CELL_PX}" height="{CELL_PX}" fill="{}"/>"#,
+            hex(rgb(bg, (0, 0, 0))),
+        );
+        if let ' ' | '#' = c {
+            continue;
+        }
+        let _ = writeln!(
+            ret,
+            r#"<text x="{}" y="{}" fill="{}">{c}</text>"#,
+            x + CELL_PX / 2,
+            y + CELL_PX / 2,
+            hex(rgb(fg, (255, 255, 255))),
+        );
+    }
+    ret.push_str("</svg>\n");
+    ret
+}
+
+pub fn write_svg(game: &GameState, args: &MinesweeperArgs, path: &Path) -> io::Result<()> {
+    std::fs::write(path, svg(game, args))
+}
+
+/// The board as text with ansi escape codes, colored like the terminal ui
+pub fn ansi(game: &GameState, args: &MinesweeperArgs) -> String {
+    let theme = Theme::from(args.theme);
+    let mut ret = String::new();
+    for line in game.cells.chunks_exact(args.width as usize) {
+        for cell in line {
+            let (c, fg, bg, modifier) = theme.tile_style(cell);
+            let mut style = ContentStyle::new();
+            style.foreground_color = Some(fg.into());
+            style.background_color = Some(bg.into());
+            if modifier.contains(Modifier::BOLD) {
+                style.attributes.set(Attribute::Bold);
+            }
+            let _ = write!(ret, "{}", style.apply(c));
+        }
+        ret.push('\n');
+    }
+    ret
+}
+
+/// 3x5 bitmaps, one row per byte with the leftmost pixel in the highest of the 3 bits
+#[cfg(feature = "image")]
+fn glyph(c: char) -> Option<[u8; 5]> {
+    Some(match c {
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '#' => [0b101, 0b111, 0b101, 0b111, 0b101],
+        '!' => [0b010, 0b010, 0b010, 0b000, 0b010],
+        '?' => [0b111, 0b001, 0b011, 0b000, 0b010],
+        '*' => [0b101, 0b010, 0b111, 0b010, 0b101],
+        _ => return None,
+    })
+}
+
+/// Renders the board as a png, one block per cell using the same colors as the terminal
+#[cfg(feature = "image")]
+pub fn write_png(game: &GameState, args: &MinesweeperArgs, path: &Path) -> io::Result<()> {
+    let w = args.width as usize;
+    let h = args.height as usize;
+    let (img_w, img_h) = (w * CELL_PX, h * CELL_PX);
+    let mut data = vec![0u8; img_w * img_h * 3];
+    let theme = Theme::from(args.theme);
+
+    let glyph_x0 = (CELL_PX - 3 * GLYPH_SCALE) / 2;
+    let glyph_y0 = (CELL_PX - 5 * GLYPH_SCALE) / 2;
+
+    for (i, cell) in game.cells.iter().enumerate() {
+        let (c, fg, bg, _) = theme.tile_style(cell);
+        let fg = rgb(fg, (255, 255, 255));
+        let bg = rgb(bg, (0, 0, 0));
+        let bitmap = glyph(c);
+        let (cx, cy) = (i % w * CELL_PX, i / w * CELL_PX);
+
+        for py in 0..CELL_PX {
+            for px in 0..CELL_PX {
+                let lit = bitmap.is_some_and(|rows| {
+                    let (gx, gy) = (px.wrapping_sub(glyph_x0), py.wrapping_sub(glyph_y0));
+                    gx < 3 * GLYPH_SCALE
+                        && gy < 5 * GLYPH_SCALE
+                        && rows[gy / GLYPH_SCALE] & (0b100 >> (gx / GLYPH_SCALE)) != 0
+                });
+                let (r, g, b) = if lit { fg } else { bg };
+                let o = ((cy + py) * img_w + cx + px) * 3;
+                data[o..o + 3].copy_from_slice(&[r, g, b]);
+            }
+        }
+    }
+
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(file, img_w as u32, img_h as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    writer.write_image_data(&data).map_err(io::Error::other)
+}
@@ -1,4 +1,6 @@
-#[derive(Copy, Clone, Debug, PartialEq)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Flag {
     Clear,
     Flagged,
@@ -8,6 +10,15 @@ pub enum Flag {
 impl Flag {
     // man this sucks, you'd think compile time sized enums would be a given
     const SIZE: u32 = Self::FlaggedMaybe as u32 + 1;
+    /// The flag that flagging this cell again gives, going round all three
+    ///
+    /// ```
+    /// use minesweeper::flag::Flag::{Clear, Flagged, FlaggedMaybe};
+    ///
+    /// assert_eq!(Clear.next(), Flagged);
+    /// assert_eq!(Flagged.next(), FlaggedMaybe);
+    /// assert_eq!(FlaggedMaybe.next(), Clear);
+    /// ```
     pub fn next(self) -> Self {
         // you'd also think "if enum to int is allowed, so is int to enum", well think again
         let next = (self as u32 + 1) % Self::SIZE;
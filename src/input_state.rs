@@ -1,6 +1,6 @@
 use crate::action::Action;
 
-#[derive(Default, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct InputState {
     pub cursor: (u16, u16),
     pub action: Option<Action>,
@@ -0,0 +1,24 @@
+//! The minesweeper engine, the terminal front-end lives in the binary.
+//!
+//! A game is a [`minesweeper::Minesweeper`] built from [`args::MinesweeperArgs`], driven by
+//! setting `input_state.action` and calling [`minesweeper::Minesweeper::update`].
+pub mod action;
+pub mod args;
+pub mod cell;
+pub mod cell_content;
+pub mod config;
+pub mod diff;
+pub mod export;
+pub mod flag;
+pub mod input_state;
+pub mod math_util;
+pub mod minesweeper;
+pub mod paths;
+pub mod script;
+pub mod solver;
+pub mod spectate;
+pub mod stats;
+pub mod theme;
+pub mod tile_visibility;
+pub mod util;
+pub mod win_state;
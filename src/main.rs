@@ -1,20 +1,96 @@
-use args::MinesweeperArgs;
-use clap::Parser;
-mod action;
-mod args;
-mod cell;
-mod cell_content;
-mod diff;
-mod flag;
-mod input_state;
-mod math_util;
-mod minesweeper;
-mod tile_visibility;
+use minesweeper::args::{Cli, CliCommand, MinesweeperArgs};
+use minesweeper::stats::Stats;
+use minesweeper::{export, script, stats};
+use std::io;
+use std::io::{BufRead, Write};
+use std::path::Path;
+mod cast;
+mod clipboard;
 mod ui;
-mod util;
-mod win_state;
 
 fn main() {
-    let args = MinesweeperArgs::parse();
-    ui::main(args).unwrap()
+    let cli = Cli::parse_args();
+    if let (true, Err(e)) = (cli.strict, cli.game.validate()) {
+        eprintln!("error: {e}");
+        std::process::exit(2);
+    }
+    match (cli.command, &cli.script) {
+        (Some(CliCommand::Stats { reset, yes }), _) => {
+            stats_command(reset, yes).unwrap_or_else(|e| {
+                eprintln!("error: {e}");
+                std::process::exit(1)
+            })
+        }
+        (None, Some(path)) => script_command(cli.game, path),
+        (None, None) => {
+            // a bad --config or address is the user's to fix, not a crash
+            let game = ui::main(&cli).unwrap_or_else(|e| {
+                eprintln!("error: {e}");
+                std::process::exit(1)
+            });
+            if cli.print {
+                print!("{}", export::ansi(&game.game_state, &game.args));
+            }
+            if let Some(path) = &cli.export_svg
+                && let Err(e) = export::write_svg(&game.game_state, &game.args, path)
+            {
+                eprintln!("error: {}: {e}", path.display());
+                std::process::exit(1);
+            }
+            #[cfg(feature = "image")]
+            if let Some(path) = &cli.export_png
+                && let Err(e) = export::write_png(&game.game_state, &game.args, path)
+            {
+                eprintln!("error: {}: {e}", path.display());
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+fn script_command(args: MinesweeperArgs, path: &Path) {
+    let text = std::fs::read_to_string(path).unwrap_or_else(|e| {
+        eprintln!("error: {}: {e}", path.display());
+        std::process::exit(1)
+    });
+    let game = script::run(args, &text).unwrap_or_else(|e| {
+        eprintln!("{}: {e}", path.display());
+        std::process::exit(1)
+    });
+    print!("{game}");
+    println!("{:?}", game.game_state.win_state);
+}
+
+fn stats_command(reset: bool, yes: bool) -> io::Result<()> {
+    if !reset {
+        let Stats {
+            played,
+            won,
+            lost,
+            streak,
+            no_flag_wins,
+        } = Stats::load();
+        println!("played: {played}\nwon: {won}\nlost: {lost}\nstreak: {streak}");
+        println!("no flag wins: {no_flag_wins}");
+        return Ok(());
+    }
+    if !yes {
+        print!("Reset all statistics? [y/N] ");
+        io::stdout().flush()?;
+        let mut answer = String::new();
+        io::stdin().lock().read_line(&mut answer)?;
+        if !matches!(answer.trim(), "y" | "Y" | "yes") {
+            println!("Nothing was reset");
+            return Ok(());
+        }
+    }
+    let removed = stats::reset()
+        .map_err(|e| io::Error::new(e.kind(), format!("can't reset the statistics: {e}")))?;
+    if removed.is_empty() {
+        println!("No statistics to reset");
+    }
+    for path in removed {
+        println!("Removed {}", path.display());
+    }
+    Ok(())
 }
@@ -1,6 +1,22 @@
 use std::ops::Sub;
 
-#[allow(dead_code)]
+/// How far `x` is outside the range, negative below it, positive above it and 0 inside
+///
+/// ```
+/// use minesweeper::math_util::dist_to_range;
+///
+/// assert_eq!(dist_to_range(2, 5, 10), -3);
+/// assert_eq!(dist_to_range(5, 5, 10), 0);
+/// assert_eq!(dist_to_range(7, 5, 10), 0);
+/// assert_eq!(dist_to_range(10, 5, 10), 0);
+/// assert_eq!(dist_to_range(14, 5, 10), 4);
+///
+/// assert_eq!(dist_to_range(-12, -10, -5), -2);
+/// assert_eq!(dist_to_range(-7, -10, -5), 0);
+/// assert_eq!(dist_to_range(-1, -10, -5), 4);
+/// assert_eq!(dist_to_range(-3, -2, 2), -1);
+/// assert_eq!(dist_to_range(3, -2, 2), 1);
+/// ```
 pub fn dist_to_range<T: PartialOrd + Sub<Output = T> + Default>(
     x: T,
     start_inclusive: T,
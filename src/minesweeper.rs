@@ -1,37 +1,71 @@
 use crate::action::Action::*;
-use crate::action::Cursor;
 use crate::action::DebugAction::*;
 use crate::action::GameCommand::*;
 use crate::action::RestartAction::*;
-use crate::args::MinesweeperArgs;
+use crate::action::expand_cell_diff_result;
+use crate::action::{Cursor, GameCommand};
+use crate::args::{ArgsError, MinesweeperArgs, PUZZLE_MAX_CELLS, percent_of};
 use crate::cell::Cell;
 use crate::cell_content::CellContent::*;
 use crate::diff::Diff::{MultiCell, SingleCell};
 use crate::diff::{Diff, SingleCellDiff};
 use crate::flag::Flag::*;
 use crate::input_state::InputState;
+use crate::solver::{flag_remaining, solvable};
 use crate::tile_visibility::TileVisibility;
 use crate::tile_visibility::TileVisibility::Hidden;
-use crate::util::{DIRS_8, DIRS_9, fill_random, i_xy, valid_neighbors, xy_i};
+use crate::util::{DIRS_8, fill_random, i_xy, safe_square, valid_neighbors, xy_i};
 use crate::win_state::WinState;
-use crate::win_state::WinState::{Lost, Ongoing, Won};
+use crate::win_state::WinState::{Lost, Ongoing, Revealed, Won};
+use Change::*;
 use TileVisibility::Show;
 use WinState::Untouched;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 use std::cmp::{max, min};
 use std::default::Default;
 use std::fmt;
 use std::fmt::{Display, Formatter};
+use std::time::{Duration, Instant};
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct Minesweeper {
     pub args: MinesweeperArgs,
     pub history: History,
     pub game_state: GameState,
     pub input_state: InputState,
     pub display: DisplayText,
+    pub stats_recorded: bool,
+    /// seed the mines are placed with, either `args.seed` or a random one
+    pub seed: u64,
+    /// when the first cell was opened
+    pub started: Option<Instant>,
+    /// how long the game took, once it is won or lost
+    pub finished: Option<Duration>,
+    /// labelled times marked while playing, for practicing a route
+    pub splits: Vec<(String, Duration)>,
+    /// time added by undos, `args.undo_penalty` seconds each
+    pub penalty: Duration,
+    /// mines opened by the move that lost the game, kept while that move can be redone
+    pub detonated: Vec<usize>,
+    /// `--puzzle` found no layout within its tries and placed an ordinary board that may need guessing
+    pub puzzle_fallback: bool,
 }
 
-#[derive(Debug, Default)]
+/// What an [`Minesweeper::update`] did to the board
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Change {
+    /// `history.last_applied()` was applied, either as a new move or as a redo
+    Applied,
+    /// `history.last_undone()` was undone
+    Undone,
+    /// A new board replaced the old one
+    Restarted,
+}
+
+#[derive(Clone, Debug, Default)]
 pub struct DisplayText {
     pub text_top: &'static str,
     pub title: &'static str,
@@ -41,10 +75,53 @@ pub struct DisplayText {
     pub mines_digits: usize,
 }
 
-#[derive(Debug, Default)]
+/// Every move of the current board as the [`Diff`] it made, the stream a front-end or a
+/// [spectator](crate::spectate) can mirror the board from without re-deriving it.
+///
+/// After each [`Minesweeper::update`] the [`Change`] it returns says which diff to pass on:
+/// [`History::diff_of`] gives the one to [`GameState::apply`] for [`Change::Applied`], and the one to
+/// [`GameState::undo`] for [`Change::Undone`]. The mines are placed by the first open without a diff,
+/// so a mirror knows the content of the cells the diffs touched, the rest stays hidden to it.
+/// A [`Change::Restarted`] starts a new board and a new history.
+///
+/// ```
+/// use minesweeper::action::{Action::*, DebugAction::Undo, GameCommand::OpenCell};
+/// use minesweeper::args::MinesweeperArgs;
+/// use minesweeper::minesweeper::{Change, Minesweeper};
+///
+/// let args = MinesweeperArgs::builder().seed(7).build().unwrap();
+/// let mut game = Minesweeper::new(args);
+/// // same size and mines, but it never places them
+/// let mut mirror = Minesweeper::new(args);
+///
+/// let mut forward = |game: &mut Minesweeper, mirror: &mut Minesweeper| {
+///     let change = game.update().unwrap();
+///     let diff = game.history.diff_of(change).unwrap();
+///     match change {
+///         Change::Applied => mirror.game_state.apply(diff),
+///         Change::Undone => mirror.game_state.undo(diff),
+///         Change::Restarted => unreachable!(),
+///     }
+/// };
+/// for (x, y) in [(16, 8), (0, 0), (31, 15)] {
+///     game.input_state.action = Some(Command(OpenCell((x, y))));
+///     forward(&mut game, &mut mirror);
+/// }
+/// game.input_state.action = Some(Debug(Undo));
+/// forward(&mut game, &mut mirror);
+///
+/// for (cell, seen) in game.game_state.cells.iter().zip(&mirror.game_state.cells) {
+///     assert_eq!(cell.visibility, seen.visibility);
+/// }
+/// assert_eq!(game.history.applied().len(), 2);
+/// assert_eq!(game.game_state.closed_empty_cells, mirror.game_state.closed_empty_cells);
+/// ```
+#[derive(Clone, Debug, Default)]
 pub struct History {
+    /// Every move in the order it was first played, including the undone ones that can still be redone
     pub entries: Vec<Diff>,
-    // from the back
+    /// How many entries, counted from the back, are undone.
+    /// A new move drops them, a redo applies the oldest of them again.
     pub index: usize,
 }
 
@@ -54,34 +131,81 @@ impl History {
         self.index = 0;
         self.entries.push(diff);
     }
-    fn step_forward(&mut self, game: &mut GameState) {
+    fn step_forward(&mut self, game: &mut GameState) -> bool {
         let mut i = self.index;
         if i == 0 {
-            return;
+            return false;
         }
         i -= 1;
         self.index = i;
 
         let ri = self.entries.len() - i - 1;
         game.apply(&self.entries[ri]);
+        true
     }
-    fn step_back(&mut self, game: &mut GameState) {
+    fn step_back(&mut self, game: &mut GameState) -> bool {
         if self.index >= self.entries.len() {
-            return;
+            return false;
         }
         let ri = self.entries.len() - self.index - 1;
         self.index += 1;
         game.undo(&self.entries[ri]);
+        true
+    }
+    /// Entries currently applied, oldest first
+    pub fn applied(&self) -> &[Diff] {
+        &self.entries[..self.entries.len() - self.index]
+    }
+    /// The entry applied most recently, `None` when everything was undone
+    pub fn last_applied(&self) -> Option<&Diff> {
+        let ri = self.entries.len().checked_sub(self.index + 1)?;
+        Some(&self.entries[ri])
+    }
+    /// Entries currently undone, the next one to redo first
+    pub fn undone(&self) -> impl Iterator<Item = &Diff> {
+        self.entries[self.entries.len() - self.index..].iter()
+    }
+    /// The entry `change` was about, `None` for a restart
+    pub fn diff_of(&self, change: Change) -> Option<&Diff> {
+        match change {
+            Change::Applied => self.last_applied(),
+            Change::Undone => self.last_undone(),
+            Change::Restarted => None,
+        }
+    }
+    /// The entry undone most recently, `None` when there is nothing to redo
+    pub fn last_undone(&self) -> Option<&Diff> {
+        if self.index == 0 {
+            return None;
+        }
+        Some(&self.entries[self.entries.len() - self.index])
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct GameState {
     pub win_state: WinState,
     pub cells: Vec<Cell>,
     pub flagged_cells: u32,
     pub closed_empty_cells: u32,
     pub open_mine_cells: u32,
+    pub unflagged_mines: u32,
+    pub win_on_flags: bool,
+    /// Opened mines are mistakes instead of losses
+    pub practice: bool,
+    /// The game is lost once this many mines are open
+    pub lives: u32,
+    /// No flag on an empty cell, no opened mine and no click that opened nothing so far
+    pub perfect: bool,
+    pub moves: u32,
+    /// Applied moves that placed a flag, a no flag run lasts while there are none.
+    /// Flags placed once only mines are left hidden, like the ones `--auto-complete` places, don't count.
+    pub flag_moves: u32,
+    /// When each cell was first opened since `clock`, empty unless `--open-times` is on
+    pub opened_at: Vec<Option<Duration>>,
+    /// Start of the game the open times count from
+    #[serde(skip)]
+    pub clock: Option<Instant>,
 }
 
 impl Minesweeper {
@@ -93,8 +217,16 @@ impl Minesweeper {
 }
 
 impl Minesweeper {
+    /// Like [`Self::new`], but refuses arguments that would have to be clamped
+    pub fn try_new(args: MinesweeperArgs) -> Result<Self, ArgsError> {
+        args.validate()?;
+        Ok(Self::new(args))
+    }
+
+    /// A new board, out of range arguments are clamped into range
     pub fn new(args: MinesweeperArgs) -> Self {
         let args = args.clamped();
+        let seed = args.seed.unwrap_or_else(rand::random);
         let width = args.width;
         let height = args.height;
         let mines = args.mines;
@@ -134,84 +266,321 @@ impl Minesweeper {
             mines_digits,
         };
 
-        let game_state = GameState {
+        let mut game_state = GameState {
             cells: vec![Cell::default(); size as usize],
             closed_empty_cells: size - mines,
+            unflagged_mines: mines,
+            win_on_flags: args.win_on_flags,
+            practice: args.practice,
+            lives: args.lives,
+            perfect: true,
+            opened_at: if args.open_times {
+                vec![None; size as usize]
+            } else {
+                vec![]
+            },
             ..GameState::default()
         };
 
-        Self {
+        if args.reveal_all {
+            // counters are left alone, Revealed never goes through apply_state
+            initialize(&mut game_state.cells, (width / 2, height / 2), args, seed);
+            for cell in &mut game_state.cells {
+                cell.visibility = Show;
+            }
+            game_state.win_state = Revealed;
+        }
+
+        let mut game = Self {
             args,
             display,
             game_state,
+            seed,
             ..Self::default()
+        };
+        if args.auto_start {
+            game.auto_start();
         }
+        if args.reveal > 0 {
+            game.reveal_start(args.reveal);
+        }
+        game
     }
 
-    pub fn update(&mut self) {
-        let Some(n) = self.input_state.action else {
-            return;
-        };
+    /// Opens a cell picked from the seed, the safe radius makes sure it is a zero
+    fn auto_start(&mut self) {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let cursor = (
+            rng.random_range(0..self.args.width),
+            rng.random_range(0..self.args.height),
+        );
+        self.input_state.cursor = cursor;
+        self.input_state.action = Some(Command(OpenCell(cursor)));
+        self.update();
+    }
+
+    /// Opens random zero regions, then random empty cells, until `percent` of the empty cells are open.
+    /// The cells come from the seed and the head start can't be undone. It stops short of
+    /// an open that would win the game, so at least one empty cell is left to the player.
+    fn reveal_start(&mut self, percent: u8) {
+        let (w, h) = (self.args.width, self.args.height);
+        let empty = self.game_state.cells.len() as u32 - self.args.mines;
+        let target = empty - percent_of(empty, percent as f32);
+        let mut rng = StdRng::seed_from_u64(self.seed.wrapping_add(1));
+        let mut order: Vec<usize> = (0..self.game_state.cells.len()).collect();
+        order.shuffle(&mut rng);
+        // the sort is stable, so the zeros stay shuffled among themselves
+        order.sort_by_key(|&i| self.game_state.cells[i].content != Empty(0));
+        for i in order {
+            if self.game_state.closed_empty_cells <= target {
+                break;
+            }
+            if let (Hidden(_), Empty(_)) = (
+                self.game_state.cells[i].visibility,
+                self.game_state.cells[i].content,
+            ) {
+                self.play(OpenCell(i_xy(i, w, h).unwrap()));
+            }
+            if !matches!(self.game_state.win_state, Ongoing) {
+                self.history.step_back(&mut self.game_state);
+                self.finished = None;
+                break;
+            }
+        }
+        self.history = History::default();
+        self.game_state.moves = 0;
+    }
+
+    pub fn update(&mut self) -> Option<Change> {
+        let n = self.input_state.action?;
         let args @ MinesweeperArgs {
             mines,
             width: w,
             height: h,
+            min_size,
+            max_size,
+            ..
         } = self.args;
-        match n {
+        let change = match n {
             Command(a) => 'b: {
                 if let (OpenCell(cursor), Untouched) = (a, self.game_state.win_state) {
                     // initialization
-                    if let None = xy_i(cursor, w, h) {
-                        break 'b;
+                    if xy_i(cursor, w, h).is_none() {
+                        break 'b None;
                     }
-                    initialize(&mut self.game_state.cells, cursor, args);
+                    let puzzle = initialize(&mut self.game_state.cells, cursor, args, self.seed);
+                    self.puzzle_fallback = args.puzzle && !puzzle;
                     self.game_state.win_state = Ongoing;
+                    self.started = Some(Instant::now());
+                    self.game_state.clock = self.started;
                 }
 
                 let Some(diff) = a.apply(&mut self.game_state, &self.args) else {
-                    break 'b;
+                    // clicks on a finished board don't count against it
+                    if let (OpenCell(_) | Chord(_), Ongoing) = (a, self.game_state.win_state) {
+                        self.game_state.perfect = false;
+                    }
+                    break 'b None;
                 };
+                let mut diff = diff;
+                if diff_has_mistake(&diff) {
+                    self.game_state.perfect = false;
+                } else if self.args.auto_complete
+                    && self.args.win_on_flags
+                    && let Some(flags) = flag_remaining(&mut self.game_state.cells, mines)
+                {
+                    // a classic game is already won once only mines are left hidden, so this only
+                    // runs where the flags decide the win. part of the same move, one undo takes both back
+                    diff = diff.extended(flags);
+                }
+                self.detonated = vec![];
+                let opened = opened_mines(&diff);
+                if !matches!(a, Surrender)
+                    && !opened.is_empty()
+                    && self.game_state.lost_with(opened.len() as u32)
+                {
+                    // the other mines come out in the same move, flags and hidden empty cells stay
+                    diff = diff.extended(reveal_mines(&mut self.game_state.cells));
+                    self.detonated = opened;
+                }
                 self.game_state.apply(&diff);
                 self.history.push(diff);
+                Some(Applied)
             }
             Restart(option) => {
                 if let Some(action) = option {
                     match action {
                         IncrementMinesPercent(unit) => {
+                            // step to the next whole percent, by at least one mine on small boards,
+                            // new() clamps the result to [1, max mines]
                             let size: u32 = w as u32 * h as u32;
-                            let hundredth = max(1, size / 100);
-                            let increment = unit as i32;
-                            self.args.mines = if increment > 0 {
-                                (mines + 1).next_multiple_of(hundredth)
+                            let percent = (mines as f32 * 100.0 / size as f32).round();
+                            let stepped = percent_of(size, percent + unit as i32 as f32);
+                            self.args.mines = if unit as i32 > 0 {
+                                stepped.max(mines + 1)
                             } else {
-                                mines.saturating_sub(hundredth).next_multiple_of(hundredth)
+                                stepped.min(mines.saturating_sub(1))
                             };
                         }
                         ResizeH(dx) => {
-                            self.args.width = self.args.width.saturating_add_signed(dx as i16);
+                            let width =
+                                w.saturating_add_signed(dx as i16).clamp(min_size, max_size);
+                            self.args.width = width;
+                            if self.args.lock_aspect {
+                                self.args.height = scale_side(h, width, w, &args);
+                            }
                         }
                         ResizeV(dy) => {
-                            self.args.height = self.args.height.saturating_add_signed(dy as i16);
+                            let height =
+                                h.saturating_add_signed(dy as i16).clamp(min_size, max_size);
+                            self.args.height = height;
+                            if self.args.lock_aspect {
+                                self.args.width = scale_side(w, height, h, &args);
+                            }
                         }
                         IncrementMines(sign) => {
                             self.args.mines = self.args.mines.saturating_add_signed(sign as i32);
                         }
+                        SetMines(mines) => self.args.mines = mines,
+                    }
+                    if let (ResizeH(_) | ResizeV(_), true) = (action, self.args.keep_density) {
+                        // new() clamps it to what fits the resized board
+                        let size = w as f64 * h as f64;
+                        let resized = self.args.width as f64 * self.args.height as f64;
+                        self.args.mines = (mines as f64 * resized / size).round() as u32;
                     }
                 }
                 let cursor = self.input_state.cursor;
                 *self = Self::new(self.args);
-                self.input_state.cursor = (
-                    cursor.0.clamp(0, self.args.width - 1),
-                    cursor.1.clamp(0, self.args.height - 1),
-                );
+                if !self.args.auto_start {
+                    self.input_state.cursor = (
+                        cursor.0.clamp(0, self.args.width - 1),
+                        cursor.1.clamp(0, self.args.height - 1),
+                    );
+                }
+                Some(Restarted)
             }
             Debug(a) => match a {
-                Undo => self.history.step_back(&mut self.game_state),
-                Redo => self.history.step_forward(&mut self.game_state),
+                Undo => {
+                    let undone = self.history.step_back(&mut self.game_state);
+                    if undone {
+                        self.penalty += Duration::from_secs(self.args.undo_penalty as u64);
+                    }
+                    undone.then_some(Undone)
+                }
+                Redo => self
+                    .history
+                    .step_forward(&mut self.game_state)
+                    .then_some(Applied),
             },
         };
 
+        // undoing out of a finished game lets the clock run again
+        match (self.game_state.win_state, self.finished) {
+            (Won | Lost, None) => self.finished = Some(self.elapsed()),
+            (Ongoing, Some(_)) => self.finished = None,
+            _ => {}
+        }
+
         self.input_state.action = None;
+        change
+    }
+
+    /// Marks the current time with `label`, only while the game is being played
+    pub fn split(&mut self, label: impl Into<String>) -> bool {
+        if !matches!(self.game_state.win_state, Ongoing) {
+            return false;
+        }
+        self.splits.push((label.into(), self.elapsed()));
+        true
+    }
+
+    /// Time since the first open plus the undo penalty, frozen once the game is won or lost
+    pub fn elapsed(&self) -> Duration {
+        self.finished
+            .or_else(|| self.started.map(|started| started.elapsed() + self.penalty))
+            .unwrap_or_default()
+    }
+
+    /// Plays a command right away, returning what it changed.
+    /// `None` when the command did nothing, like opening an open cell or playing a finished game.
+    pub fn play(&mut self, command: GameCommand) -> Option<&Diff> {
+        self.input_state.action = Some(Command(command));
+        match self.update() {
+            Some(Applied) => self.history.last_applied(),
+            _ => None,
+        }
+    }
+
+    /// Opens the cell at `(x, y)`.
+    ///
+    /// The first open of a board places the mines, keeping the cells within
+    /// `args.safe_radius` of `(x, y)` free of them, so the first open never loses.
+    ///
+    /// Opening a mine that loses the game opens the other unflagged mines in the same move,
+    /// the flags and the hidden empty cells stay as they were.
+    ///
+    /// ```
+    /// use minesweeper::action::{Action::Debug, DebugAction::Undo};
+    /// use minesweeper::args::{Difficulty, MinesweeperArgs};
+    /// use minesweeper::cell_content::CellContent::Mine;
+    /// use minesweeper::flag::Flag::{Clear, Flagged};
+    /// use minesweeper::minesweeper::Minesweeper;
+    /// use minesweeper::tile_visibility::TileVisibility::{Hidden, Show};
+    /// use minesweeper::win_state::WinState;
+    ///
+    /// let args = MinesweeperArgs::builder().difficulty(Difficulty::Intermediate).seed(5).build().unwrap();
+    /// let mut game = Minesweeper::new(args);
+    /// game.open(8, 8);
+    /// let xy = |i: usize| ((i % 16) as u16, (i / 16) as u16);
+    /// let hidden = |game: &Minesweeper, mine: bool| {
+    ///     let cells = &game.game_state.cells;
+    ///     (0..cells.len())
+    ///         .find(|&i| cells[i].visibility == Hidden(Clear) && (cells[i].content == Mine) == mine)
+    ///         .unwrap()
+    /// };
+    /// let (wrong, right) = (hidden(&game, false), hidden(&game, true));
+    /// game.flag(xy(wrong).0, xy(wrong).1);
+    /// game.flag(xy(right).0, xy(right).1);
+    /// let before = game.game_state.clone();
+    ///
+    /// let boom = hidden(&game, true);
+    /// game.open(xy(boom).0, xy(boom).1);
+    /// let cells = &game.game_state.cells;
+    /// assert!(matches!(game.game_state.win_state, WinState::Lost));
+    /// assert_eq!(game.detonated, [boom]);
+    /// assert_eq!(cells[wrong].visibility, Hidden(Flagged));
+    /// assert_eq!(cells[right].visibility, Hidden(Flagged));
+    /// let open_mines = cells.iter().filter(|c| (c.visibility, c.content) == (Show, Mine)).count();
+    /// assert_eq!(open_mines, 39);
+    /// assert!(cells.iter().any(|c| c.content != Mine && c.visibility == Hidden(Clear)));
+    /// // the loss and the reveal are one move
+    /// assert_eq!(game.game_state.moves, before.moves + 1);
+    /// assert_eq!(game.history.applied().len(), before.moves as usize + 1);
+    ///
+    /// game.input_state.action = Some(Debug(Undo));
+    /// game.update();
+    /// assert_eq!(game.game_state.cells, before.cells);
+    /// assert_eq!(game.game_state.moves, before.moves);
+    /// assert_eq!(game.game_state.open_mine_cells, before.open_mine_cells);
+    /// ```
+    pub fn open(&mut self, x: u16, y: u16) -> Option<&Diff> {
+        self.play(OpenCell((x, y)))
+    }
+
+    /// Cycles the flag of the cell at `(x, y)`, does nothing before the first open.
+    pub fn flag(&mut self, x: u16, y: u16) -> Option<&Diff> {
+        self.play(FlagCell((x, y)))
+    }
+
+    /// Opens the unflagged neighbors of the number at `(x, y)` once it has as many flags around it.
+    pub fn chord(&mut self, x: u16, y: u16) -> Option<&Diff> {
+        self.play(Chord((x, y)))
+    }
+
+    /// Opens the whole board, losing the game.
+    pub fn surrender(&mut self) -> Option<&Diff> {
+        self.play(Surrender)
     }
 
     pub fn move_cursor(&mut self, dx: i32, dy: i32) {
@@ -230,19 +599,156 @@ impl Minesweeper {
     }
 }
 
-fn initialize(cells: &mut Vec<Cell>, cursor: Cursor, args: MinesweeperArgs) {
+/// Does the diff put a flag on a cell that had none?
+fn places_flag(diff: &Diff) -> bool {
+    let flags = |diff: &SingleCellDiff| {
+        diff.after.visibility == Hidden(Flagged) && diff.before.visibility != Hidden(Flagged)
+    };
+    match diff {
+        SingleCell(diff) => flags(diff),
+        MultiCell(diffs) => diffs.iter().any(flags),
+    }
+}
+
+/// Does the diff flag an empty cell or open a mine?
+fn diff_has_mistake(diff: &Diff) -> bool {
+    let mistake = |diff: &SingleCellDiff| {
+        matches!(
+            (diff.after.visibility, diff.after.content),
+            (Hidden(Flagged), Empty(_)) | (Show, Mine)
+        )
+    };
+    match diff {
+        SingleCell(diff) => mistake(diff),
+        MultiCell(diffs) => diffs.iter().any(mistake),
+    }
+}
+
+/// Index of every mine the diff opens
+fn opened_mines(diff: &Diff) -> Vec<usize> {
+    let opened = |diff: &&SingleCellDiff| {
+        matches!(diff.before.visibility, Hidden(_))
+            && matches!((diff.after.visibility, diff.after.content), (Show, Mine))
+    };
+    match diff {
+        SingleCell(diff) => [diff].into_iter().filter(opened).map(|d| d.index).collect(),
+        MultiCell(diffs) => diffs.iter().filter(opened).map(|d| d.index).collect(),
+    }
+}
+
+/// Opens the hidden mines that aren't flagged, to show a lost game
+fn reveal_mines(cells: &mut [Cell]) -> Vec<SingleCellDiff> {
+    let mut ret = vec![];
+    for (i, cell) in cells.iter_mut().enumerate() {
+        if let (Hidden(Clear | FlaggedMaybe), Mine) = (cell.visibility, cell.content) {
+            ret.push(cell.diff(i, Show));
+        }
+    }
+    ret
+}
+
+/// Places the mines, returns whether they form a `--puzzle` layout
+fn initialize(cells: &mut [Cell], cursor: Cursor, args: MinesweeperArgs, seed: u64) -> bool {
     let m = args.mines;
     let w = args.width;
     let h = args.height;
-    let neighbors = valid_neighbors(&DIRS_9, cursor, w, h);
 
-    let mines = fill_random(
-        neighbors.map(|cursor| xy_i(cursor, w, h).unwrap()),
-        w as usize * h as usize,
-        m as usize,
-        false,
-        true,
-    );
+    let mines = if args.puzzle {
+        puzzle_layout(cursor, args, seed)
+    } else {
+        None
+    };
+    let puzzle = mines.is_some();
+    let mines = mines.unwrap_or_else(|| {
+        let neighbors = safe_square(cursor, args.safe_radius as u16, w, h);
+        fill_random(
+            &mut StdRng::seed_from_u64(seed),
+            neighbors.map(|cursor| xy_i(cursor, w, h).unwrap()),
+            w as usize * h as usize,
+            m as usize,
+            false,
+            true,
+        )
+    });
+    place_mines(cells, &mines, w, h);
+    puzzle
+}
+
+/// Layouts tried for `--puzzle` before settling for a random one, on boards up to
+/// [`PUZZLE_FULL_CELLS`] cells
+const PUZZLE_TRIES: usize = 2000;
+
+/// Every try solves the whole board, which takes about the square of its size,
+/// so bigger boards get that much fewer tries and the wait stays about the same
+const PUZZLE_FULL_CELLS: usize = 512;
+
+/// How many layouts [`puzzle_layout`] tries on a board of `size` cells
+fn puzzle_tries(size: usize) -> usize {
+    let scale = (PUZZLE_FULL_CELLS as f64 / size.max(1) as f64).powi(2);
+    ((PUZZLE_TRIES as f64 * scale) as usize).clamp(1, PUZZLE_TRIES)
+}
+
+/// A layout that looks the same turned upside down and can be solved from `cursor` without guessing,
+/// `None` when none turned up within [`puzzle_tries`], the mines can't be paired up or the board is
+/// above [`PUZZLE_MAX_CELLS`]
+fn puzzle_layout(cursor: Cursor, args: MinesweeperArgs, seed: u64) -> Option<Vec<bool>> {
+    let (w, h, m) = (args.width, args.height, args.mines as usize);
+    let size = w as usize * h as usize;
+    if size > PUZZLE_MAX_CELLS as usize {
+        return None;
+    }
+    let start = xy_i(cursor, w, h)?;
+    // every mine has a twin in the mirrored cell, so neither may be in the safe square
+    let mut safe = vec![false; size];
+    for xy in safe_square(cursor, args.safe_radius as u16, w, h) {
+        let i = xy_i(xy, w, h).unwrap();
+        safe[i] = true;
+        safe[size - 1 - i] = true;
+    }
+    let pairs: Vec<usize> = (0..size / 2)
+        .filter(|&i| !safe[i] && !safe[size - 1 - i])
+        .collect();
+    if m % 2 == 1 || pairs.len() < m / 2 {
+        return None;
+    }
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    for _ in 0..puzzle_tries(size) {
+        let picked = fill_random(
+            &mut rng,
+            std::iter::empty(),
+            pairs.len(),
+            m / 2,
+            false,
+            true,
+        );
+        let mut mines = vec![false; size];
+        for (&i, _) in pairs.iter().zip(picked).filter(|(_, picked)| *picked) {
+            mines[i] = true;
+            mines[size - 1 - i] = true;
+        }
+
+        let mut cells = vec![Cell::default(); size];
+        place_mines(&mut cells, &mines, w, h);
+        if let Empty(0) = cells[start].content {
+            expand_cell_diff_result(&mut cells, w, h, start);
+        } else {
+            cells[start].visibility = Show;
+        }
+        if solvable(&cells, w, h, m as u32) {
+            return Some(mines);
+        }
+    }
+    None
+}
+
+/// Puts the mines in the cells and counts them around every empty cell
+fn place_mines(cells: &mut [Cell], mines: &[bool], w: u16, h: u16) {
+    #[cfg(feature = "parallel")]
+    if cells.len() >= PARALLEL_MIN_CELLS && rayon::current_num_threads() > 1 {
+        count_neighbors_parallel(cells, mines, w, h);
+        return;
+    }
 
     for (i, &has_mine) in mines.iter().enumerate() {
         if !has_mine {
@@ -260,6 +766,28 @@ fn initialize(cells: &mut Vec<Cell>, cursor: Cursor, args: MinesweeperArgs) {
     }
 }
 
+/// Boards smaller than this are counted on one thread. The value is a guess that hasn't been
+/// measured: it is meant to keep the default boards off the thread pool, where the counting takes
+/// well under a millisecond, while boards of 512x512 and up use every core.
+#[cfg(feature = "parallel")]
+const PARALLEL_MIN_CELLS: usize = 1 << 18;
+
+/// Every cell counts the mines around it, instead of every mine adding to its neighbors
+#[cfg(feature = "parallel")]
+fn count_neighbors_parallel(cells: &mut [Cell], mines: &[bool], w: u16, h: u16) {
+    use rayon::prelude::*;
+    cells.par_iter_mut().enumerate().for_each(|(i, cell)| {
+        cell.content = if mines[i] {
+            Mine
+        } else {
+            let n = valid_neighbors(&DIRS_8, i_xy(i, w, h).unwrap(), w, h)
+                .filter(|&xy| mines[xy_i(xy, w, h).unwrap()])
+                .count();
+            Empty(n as u8)
+        };
+    });
+}
+
 impl GameState {
     fn apply_single_diff(
         &mut self,
@@ -272,7 +800,7 @@ impl GameState {
         //let cell = &mut self.cells[*index];
         //assert_eq!(*before, *cell);
 
-        self.apply_state(before, after);
+        self.apply_state(*index, before, after);
         let cell = &mut self.cells[*index];
         *cell = *after;
     }
@@ -287,14 +815,22 @@ impl GameState {
         let cell = &self.cells[*index];
         assert_eq!(*after, *cell);
 
-        self.apply_state(after, before);
+        self.apply_state(*index, after, before);
         let cell = &mut self.cells[*index];
         *cell = *before;
     }
 
-    fn apply_state(&mut self, before: &Cell, after: &Cell) {
+    fn apply_state(&mut self, index: usize, before: &Cell, after: &Cell) {
         let visibility_diff = (before.content, before.visibility, after.visibility);
 
+        if let Some(opened_at) = self.opened_at.get_mut(index) {
+            match visibility_diff {
+                (_, Hidden(_), Show) => *opened_at = self.clock.map(|clock| clock.elapsed()),
+                (_, Show, Hidden(_)) => *opened_at = None,
+                _ => {}
+            }
+        }
+
         match visibility_diff {
             // empty
             (Empty(_), Hidden(_), Show) => self.closed_empty_cells -= 1,
@@ -311,14 +847,57 @@ impl GameState {
             _ => {}
         };
 
-        self.win_state = match (self.closed_empty_cells, self.open_mine_cells) {
-            (0, 0) => Won,
-            (_, 0) => Ongoing,
-            (_, _) => Lost,
+        match visibility_diff {
+            (Mine, Show | Hidden(FlaggedMaybe | Clear), Hidden(Flagged)) => {
+                self.unflagged_mines -= 1
+            }
+            (Mine, Hidden(Flagged), Show | Hidden(FlaggedMaybe | Clear)) => {
+                self.unflagged_mines += 1
+            }
+            _ => {}
+        };
+    }
+
+    /// Is the game lost once `more` mines are opened on top of the open ones?
+    fn lost_with(&self, more: u32) -> bool {
+        // in practice the opened mines only count as mistakes
+        !self.practice && self.open_mine_cells + more >= self.lives
+    }
+
+    /// Derives the win state from the counters, once a whole diff went through
+    fn update_win_state(&mut self) {
+        // with every empty cell open there's no room left for a misplaced flag
+        let unflagged_mines = if self.win_on_flags {
+            self.unflagged_mines
+        } else {
+            0
+        };
+        let lost = self.lost_with(0);
+        self.win_state = match (self.closed_empty_cells, unflagged_mines, lost) {
+            (_, _, true) => Lost,
+            (0, 0, false) => Won,
+            (_, _, false) => Ongoing,
         }
     }
 
-    fn apply(&mut self, diff: &Diff) {
+    /// Does the move that led to this state break a no flag run?
+    /// Once only mines are left hidden the flags give nothing away.
+    fn counts_flag(&self, diff: &Diff) -> bool {
+        places_flag(diff) && self.closed_empty_cells > 0
+    }
+
+    /// Is this still a no flag run, with no flag placed by the moves so far?
+    pub fn nf_valid(&self) -> bool {
+        self.flag_moves == 0
+    }
+
+    /// Lives left before the next opened mine loses the game
+    pub fn lives_left(&self) -> u32 {
+        self.lives.saturating_sub(self.open_mine_cells)
+    }
+
+    pub fn apply(&mut self, diff: &Diff) {
+        self.moves += 1;
         match diff {
             SingleCell(diff) => {
                 self.apply_single_diff(diff);
@@ -329,9 +908,18 @@ impl GameState {
                 }
             }
         }
+        // checked on the state after the move, undo checks the same state before taking it back
+        if self.counts_flag(diff) {
+            self.flag_moves += 1;
+        }
+        self.update_win_state();
     }
 
-    fn undo(&mut self, diff: &Diff) {
+    pub fn undo(&mut self, diff: &Diff) {
+        self.moves -= 1;
+        if self.counts_flag(diff) {
+            self.flag_moves -= 1;
+        }
         match diff {
             SingleCell(diff) => {
                 self.undo_single_diff(diff);
@@ -342,6 +930,7 @@ impl GameState {
                 }
             }
         }
+        self.update_win_state();
     }
 }
 
@@ -356,3 +945,9 @@ impl Display for Minesweeper {
         Ok(())
     }
 }
+
+/// `side` scaled by `to / from`, rounded and kept within the board size limits
+fn scale_side(side: u16, to: u16, from: u16, args: &MinesweeperArgs) -> u16 {
+    let scaled = (side as f32 * to as f32 / from as f32).round() as u16;
+    scaled.clamp(args.min_size, args.max_size)
+}
@@ -0,0 +1,18 @@
+//! Where files live: `$XDG_CONFIG_HOME/minesweeper` and `$XDG_DATA_HOME/minesweeper` on linux,
+//! the platform's equivalents elsewhere. Directories are only created when writing.
+use std::path::PathBuf;
+
+const APP: &str = "minesweeper";
+
+pub fn config_dir() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join(APP))
+}
+
+pub fn data_dir() -> Option<PathBuf> {
+    Some(dirs::data_dir()?.join(APP))
+}
+
+/// `~/.minesweeper`, where files were kept before following the platform conventions
+pub fn legacy_dir() -> Option<PathBuf> {
+    Some(dirs::home_dir()?.join(".minesweeper"))
+}
@@ -0,0 +1,63 @@
+use crate::action::Action::*;
+use crate::action::DebugAction::*;
+use crate::action::GameCommand::*;
+use crate::action::{Action, Cursor};
+use crate::args::MinesweeperArgs;
+use crate::minesweeper::Minesweeper;
+
+/// Parses one script line, blank lines and `#` comments give `None`
+///
+/// ```text
+/// open 3 5
+/// flag 2 2
+/// clear 2 2
+/// chord 4 1
+/// sweep 4 1
+/// auto
+/// finish
+/// surrender
+/// undo
+/// redo
+/// restart
+/// ```
+pub fn parse_line(line: &str) -> Result<Option<Action>, String> {
+    let line = line.split('#').next().unwrap_or_default();
+    let mut words = line.split_whitespace();
+    let Some(command) = words.next() else {
+        return Ok(None);
+    };
+    let mut cursor = || -> Result<Cursor, String> {
+        let mut coordinate = || {
+            let word = words.next().ok_or("expected 2 coordinates")?;
+            word.parse::<u16>()
+                .map_err(|_| format!("invalid coordinate `{word}`"))
+        };
+        Ok((coordinate()?, coordinate()?))
+    };
+    let action = match command {
+        "open" => Command(OpenCell(cursor()?)),
+        "flag" => Command(FlagCell(cursor()?)),
+        "clear" => Command(ClearFlag(cursor()?)),
+        "chord" => Command(Chord(cursor()?)),
+        "sweep" => Command(FlagNeighbors(cursor()?)),
+        "auto" => Command(AutoResolve),
+        "finish" => Command(Finish),
+        "surrender" => Command(Surrender),
+        "undo" => Debug(Undo),
+        "redo" => Debug(Redo),
+        "restart" => Restart(None),
+        _ => return Err(format!("unknown command `{command}`")),
+    };
+    Ok(Some(action))
+}
+
+/// Plays every line of `script` on a new game, errors point at the offending line
+pub fn run(args: MinesweeperArgs, script: &str) -> Result<Minesweeper, String> {
+    let mut game = Minesweeper::new(args);
+    for (n, line) in script.lines().enumerate() {
+        let action = parse_line(line).map_err(|e| format!("line {}: {e}", n + 1))?;
+        game.input_state.action = action;
+        game.update();
+    }
+    Ok(game)
+}
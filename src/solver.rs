@@ -0,0 +1,158 @@
+use crate::action::expand_cell_diff_result;
+use crate::cell::Cell;
+use crate::cell_content::CellContent::*;
+use crate::diff::Diff::MultiCell;
+use crate::diff::{Diff, SingleCellDiff};
+use crate::flag::Flag::*;
+use crate::minesweeper::GameState;
+use crate::tile_visibility::TileVisibility::{Hidden, Show};
+use crate::util::{DIRS_8, i_xy, valid_neighbors, xy_i};
+use std::collections::BTreeSet;
+
+/// Repeatedly applies the per-cell rules until nothing changes:
+/// - a number with as many hidden neighbors as its value has only mines around it
+/// - a number with all its mines known has only safe cells around it
+///
+/// Only mines deduced here count as known, the player's flags are not trusted.
+/// Flagged cells are never opened.
+pub fn resolve_trivial(game: &mut GameState, w: u16, h: u16) -> Option<Diff> {
+    let (ret, _) = deduce(&mut game.cells, w, h);
+    if ret.is_empty() {
+        None
+    } else {
+        Some(MultiCell(ret))
+    }
+}
+
+/// Opens every hidden cell [`resolve_trivial`] and the mine count prove safe and flags the ones they prove to be mines,
+/// only when that decides every last hidden cell. Cells the player flagged are opened if they turn out safe.
+pub fn solve_all(game: &mut GameState, w: u16, h: u16, mines: u32) -> Option<Diff> {
+    let mut cells = game.cells.clone();
+    let ret = decide(&mut cells, w, h, mines)?;
+    if ret.is_empty() {
+        return None;
+    }
+    game.cells = cells;
+    Some(MultiCell(ret))
+}
+
+/// Can the board be finished from what is open without guessing?
+pub fn solvable(cells: &[Cell], w: u16, h: u16, mines: u32) -> bool {
+    decide(&mut cells.to_vec(), w, h, mines).is_some()
+}
+
+/// The moves that finish the board, `None` when some hidden cell can't be decided
+fn decide(cells: &mut [Cell], w: u16, h: u16, mines: u32) -> Option<Vec<SingleCellDiff>> {
+    let (mut ret, known) = deduce(cells, w, h);
+    let undecided: Vec<usize> = (0..cells.len())
+        .filter(|&i| matches!(cells[i].visibility, Hidden(_)) && !known.contains(&i))
+        .collect();
+    let open_mines = cells
+        .iter()
+        .filter(|cell| matches!((cell.visibility, cell.content), (Show, Mine)))
+        .count();
+    // mines neither opened nor deduced, somewhere among the undecided cells
+    let left = (mines as usize).saturating_sub(open_mines + known.len());
+    if left == undecided.len() {
+        for j in undecided {
+            if cells[j].visibility != Hidden(Flagged) {
+                ret.push(cells[j].diff(j, Hidden(Flagged)));
+            }
+        }
+    } else if left == 0 {
+        for j in undecided {
+            match (cells[j].visibility, cells[j].content) {
+                (Show, _) => continue,
+                (Hidden(_), Empty(0)) => ret.extend(expand_cell_diff_result(cells, w, h, j)),
+                (Hidden(_), _) => ret.push(cells[j].diff(j, Show)),
+            }
+        }
+    } else {
+        return None;
+    }
+    Some(ret)
+}
+
+/// The diffs of the rules in [`resolve_trivial`] and the mines they found
+fn deduce(cells: &mut [Cell], w: u16, h: u16) -> (Vec<SingleCellDiff>, BTreeSet<usize>) {
+    let mut ret = vec![];
+    let mut mines = BTreeSet::<usize>::new();
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for i in 0..cells.len() {
+            let (Show, Empty(n)) = (cells[i].visibility, cells[i].content) else {
+                continue;
+            };
+            let neighbors: Vec<usize> = valid_neighbors(&DIRS_8, i_xy(i, w, h).unwrap(), w, h)
+                .map(|xy| xy_i(xy, w, h).unwrap())
+                .collect();
+            // mines opened in practice are already accounted for
+            let open_mines = neighbors
+                .iter()
+                .filter(|&&j| matches!((cells[j].visibility, cells[j].content), (Show, Mine)))
+                .count() as u8;
+            let n = n - open_mines;
+            let hidden: Vec<usize> = neighbors
+                .into_iter()
+                .filter(|&j| matches!(cells[j].visibility, Hidden(_)))
+                .collect();
+            if hidden.is_empty() {
+                continue;
+            }
+
+            if hidden.len() == n as usize {
+                for j in hidden {
+                    if !mines.insert(j) {
+                        continue;
+                    }
+                    changed = true;
+                    if let Hidden(Clear | FlaggedMaybe) = cells[j].visibility {
+                        ret.push(cells[j].diff(j, Hidden(Flagged)));
+                    }
+                }
+            } else if hidden.iter().filter(|j| mines.contains(j)).count() == n as usize {
+                for j in hidden {
+                    if mines.contains(&j) {
+                        continue;
+                    }
+                    match (cells[j].visibility, cells[j].content) {
+                        (Hidden(Flagged), _) => continue,
+                        (Hidden(_), Empty(0)) => {
+                            ret.extend(expand_cell_diff_result(cells, w, h, j))
+                        }
+                        (Hidden(_), _) => ret.push(cells[j].diff(j, Show)),
+                        (Show, _) => continue,
+                    }
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    (ret, mines)
+}
+
+/// Flags every hidden cell once there are exactly as many of them as mines left to find,
+/// which the player can tell from the mine count alone
+pub fn flag_remaining(cells: &mut [Cell], mines: u32) -> Option<Vec<SingleCellDiff>> {
+    let hidden = cells
+        .iter()
+        .filter(|cell| matches!(cell.visibility, Hidden(_)))
+        .count();
+    let open_mines = cells
+        .iter()
+        .filter(|cell| matches!((cell.visibility, cell.content), (Show, Mine)))
+        .count();
+    if hidden == 0 || hidden as u32 != mines.saturating_sub(open_mines as u32) {
+        return None;
+    }
+    let mut ret = vec![];
+    for (i, cell) in cells.iter_mut().enumerate() {
+        if let Hidden(Clear | FlaggedMaybe) = cell.visibility {
+            ret.push(cell.diff(i, Hidden(Flagged)));
+        }
+    }
+    (!ret.is_empty()).then_some(ret)
+}
@@ -0,0 +1,217 @@
+use crate::args::MinesweeperArgs;
+use crate::diff::Diff;
+use crate::minesweeper::Change::*;
+use crate::minesweeper::{Change, GameState, Minesweeper};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{Receiver, SyncSender, channel, sync_channel};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Bumped on any incompatible change to [`Message`]
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// One line of json on the wire
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Envelope<M> {
+    pub version: u32,
+    pub message: M,
+}
+
+/// What a watcher receives
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum Message {
+    /// Full state, sent on connect and after every restart
+    Board {
+        width: u16,
+        height: u16,
+        mines: u32,
+        game_state: GameState,
+    },
+    Apply(Diff),
+    Undo(Diff),
+}
+
+/// What the server sends, borrowing from the hosted game, has to serialize like [`Message`]
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum Outgoing<'a> {
+    Board {
+        width: u16,
+        height: u16,
+        mines: u32,
+        game_state: &'a GameState,
+    },
+    Apply(&'a Diff),
+    Undo(&'a Diff),
+}
+
+impl<'a> Outgoing<'a> {
+    pub fn board(game: &'a Minesweeper) -> Self {
+        Self::Board {
+            width: game.args.width,
+            height: game.args.height,
+            mines: game.args.mines,
+            game_state: &game.game_state,
+        }
+    }
+
+    /// The message mirroring `change`, `None` if there is nothing to send
+    pub fn from_change(game: &'a Minesweeper, change: Change) -> Option<Self> {
+        let message = match change {
+            Applied => Self::Apply(game.history.last_applied()?),
+            Undone => Self::Undo(game.history.last_undone()?),
+            Restarted => Self::board(game),
+        };
+        Some(message)
+    }
+
+    fn encode(&self) -> String {
+        let envelope = Envelope {
+            version: PROTOCOL_VERSION,
+            message: self,
+        };
+        let mut line = serde_json::to_string(&envelope).unwrap();
+        line.push('\n');
+        line
+    }
+}
+
+impl Message {
+    /// Mirrors the message on a read only copy of the game
+    pub fn apply_to(self, game: &mut Minesweeper) {
+        match self {
+            Self::Board {
+                width,
+                height,
+                mines,
+                game_state,
+            } => {
+                let cursor = game.input_state.cursor;
+                *game = Minesweeper::new(MinesweeperArgs {
+                    width,
+                    height,
+                    mines,
+                    // the served board may be outside the bounds this side was started with
+                    min_size: game.args.min_size.min(width).min(height),
+                    max_size: game.args.max_size.max(width).max(height),
+                    ..game.args
+                });
+                game.game_state = game_state;
+                game.input_state.cursor = (cursor.0.min(width - 1), cursor.1.min(height - 1));
+            }
+            Self::Apply(diff) => game.game_state.apply(&diff),
+            Self::Undo(diff) => game.game_state.undo(&diff),
+        }
+    }
+}
+
+/// Messages a watcher can fall behind by before it is dropped
+const WATCHER_QUEUE: usize = 256;
+/// A write stuck longer than this drops the watcher, ending its thread
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Streams every change of the hosted game to the connected watchers
+#[derive(Debug)]
+pub struct Server {
+    addr: SocketAddr,
+    pending: Arc<Mutex<Vec<TcpStream>>>,
+    /// Each watcher is written to by its own thread, so a slow one never holds up the game
+    watchers: Vec<SyncSender<Arc<str>>>,
+}
+
+impl Server {
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let pending = Arc::new(Mutex::new(vec![]));
+        let accepted = pending.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                accepted.lock().unwrap().push(stream);
+            }
+        });
+        Ok(Self {
+            addr,
+            pending,
+            watchers: vec![],
+        })
+    }
+
+    /// Where watchers connect, with the actual port when bound to port 0
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Watchers currently kept up to date
+    pub fn watchers(&self) -> usize {
+        self.watchers.len()
+    }
+
+    /// Sends the board to watchers that connected since the last call
+    pub fn accept(&mut self, game: &Minesweeper) {
+        let new: Vec<_> = self.pending.lock().unwrap().drain(..).collect();
+        if new.is_empty() {
+            return;
+        }
+        let line: Arc<str> = Outgoing::board(game).encode().into();
+        for stream in new {
+            let Ok(watcher) = spawn_writer(stream) else {
+                continue;
+            };
+            if watcher.try_send(line.clone()).is_ok() {
+                self.watchers.push(watcher);
+            }
+        }
+    }
+
+    /// Sends the message to every watcher, dropping the ones that disconnected or fell behind
+    pub fn broadcast(&mut self, message: &Outgoing) {
+        let line: Arc<str> = message.encode().into();
+        self.watchers
+            .retain(|watcher| watcher.try_send(line.clone()).is_ok());
+    }
+}
+
+/// Writes the lines sent through the returned channel to `stream` on a thread of its own,
+/// until the channel is dropped or a write fails
+fn spawn_writer(mut stream: TcpStream) -> io::Result<SyncSender<Arc<str>>> {
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    let (tx, rx) = sync_channel::<Arc<str>>(WATCHER_QUEUE);
+    thread::spawn(move || {
+        for line in rx {
+            if stream.write_all(line.as_bytes()).is_err() {
+                return;
+            }
+        }
+    });
+    Ok(tx)
+}
+
+/// Connects to a server, the messages it sends arrive through the returned channel
+pub fn watch(addr: impl ToSocketAddrs) -> io::Result<Receiver<Result<Message, String>>> {
+    let stream = TcpStream::connect(addr)?;
+    let (tx, rx) = channel();
+    thread::spawn(move || {
+        for line in BufReader::new(stream).lines() {
+            let message = match line {
+                Ok(line) => serde_json::from_str::<Envelope<Message>>(&line)
+                    .map_err(|e| e.to_string())
+                    .and_then(|envelope| match envelope.version {
+                        PROTOCOL_VERSION => Ok(envelope.message),
+                        v => Err(format!("unsupported protocol version {v}")),
+                    }),
+                Err(e) => Err(e.to_string()),
+            };
+            let stop = message.is_err();
+            if tx.send(message).is_err() || stop {
+                return;
+            }
+        }
+    });
+    Ok(rx)
+}
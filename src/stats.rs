@@ -0,0 +1,132 @@
+use crate::paths;
+use crate::win_state::WinState;
+use std::fs;
+use std::io;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Stats {
+    pub played: u32,
+    pub won: u32,
+    pub lost: u32,
+    /// wins in a row, reset by a loss
+    pub streak: u32,
+    /// wins without a single flag placed
+    pub no_flag_wins: u32,
+}
+
+pub fn stats_path() -> Option<PathBuf> {
+    Some(paths::data_dir()?.join("stats"))
+}
+
+/// Where stats were saved before moving to the data directory, still read when there's nothing newer
+fn legacy_stats_path() -> Option<PathBuf> {
+    Some(paths::legacy_dir()?.join("stats"))
+}
+
+impl Stats {
+    pub fn load() -> Self {
+        let Some(text) = [stats_path(), legacy_stats_path()]
+            .into_iter()
+            .flatten()
+            .find_map(|path| fs::read_to_string(path).ok())
+        else {
+            return Self::default();
+        };
+        let mut stats = Self::default();
+        for line in text.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let Ok(value) = value.trim().parse() else {
+                continue;
+            };
+            match key.trim() {
+                "played" => stats.played = value,
+                "won" => stats.won = value,
+                "lost" => stats.lost = value,
+                "streak" => stats.streak = value,
+                "no_flag_wins" => stats.no_flag_wins = value,
+                _ => {}
+            }
+        }
+        stats
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = stats_path() else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(
+            path,
+            format!(
+                "played={}\nwon={}\nlost={}\nstreak={}\nno_flag_wins={}\n",
+                self.played, self.won, self.lost, self.streak, self.no_flag_wins
+            ),
+        )
+    }
+
+    /// Counts a finished game, returning whether it was one: anything but a win or a loss is ignored
+    ///
+    /// ```
+    /// use minesweeper::stats::Stats;
+    /// use minesweeper::win_state::WinState;
+    ///
+    /// let mut stats = Stats::default();
+    /// assert!(stats.add(WinState::Won, false));
+    /// assert!(stats.add(WinState::Won, true));
+    /// assert_eq!((stats.streak, stats.won, stats.no_flag_wins), (2, 2, 1));
+    ///
+    /// assert!(!stats.add(WinState::Ongoing, true));
+    /// assert_eq!(stats.streak, 2);
+    ///
+    /// assert!(stats.add(WinState::Lost, true));
+    /// assert_eq!((stats.streak, stats.lost, stats.played), (0, 1, 3));
+    /// assert!(stats.add(WinState::Won, false));
+    /// assert_eq!(stats.streak, 1);
+    /// ```
+    pub fn add(&mut self, win_state: WinState, no_flag: bool) -> bool {
+        match win_state {
+            WinState::Won => {
+                self.won += 1;
+                self.streak += 1;
+                if no_flag {
+                    self.no_flag_wins += 1;
+                }
+            }
+            WinState::Lost => {
+                self.lost += 1;
+                self.streak = 0;
+            }
+            _ => return false,
+        }
+        self.played += 1;
+        true
+    }
+
+    /// Adds a finished game to the persisted stats, anything but a win or a loss is ignored
+    pub fn record(win_state: WinState, no_flag: bool) -> io::Result<Self> {
+        let mut stats = Self::load();
+        if stats.add(win_state, no_flag) {
+            stats.save()?;
+        }
+        Ok(stats)
+    }
+}
+
+/// Deletes the persisted stats, returning the files that were actually removed
+pub fn reset() -> io::Result<Vec<PathBuf>> {
+    let mut removed = vec![];
+    for path in [stats_path(), legacy_stats_path()].into_iter().flatten() {
+        match fs::remove_file(&path) {
+            Ok(()) => removed.push(path),
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(removed)
+}
@@ -0,0 +1,221 @@
+use crate::cell::Cell;
+use crate::cell_content::CellContent;
+use crate::flag::Flag::*;
+use crate::tile_visibility::TileVisibility::*;
+use clap::ValueEnum;
+use ratatui::style::Color::*;
+use ratatui::style::{Color, Modifier};
+
+#[derive(ValueEnum, Copy, Clone, Default, Debug)]
+pub enum ThemeName {
+    #[default]
+    Default,
+    Dark,
+    Light,
+    HighContrast,
+}
+
+/// Colors the board is drawn with, as (foreground, background) where there's a glyph
+#[derive(Copy, Clone, Debug)]
+pub struct Theme {
+    pub hidden: (Color, Color),
+    pub flagged: (Color, Color),
+    pub flagged_maybe: (Color, Color),
+    pub mine: (Color, Color),
+    /// background of every revealed empty cell
+    pub revealed: Color,
+    /// background of the cell under the cursor
+    pub cursor: Color,
+    /// background of the cells the last move changed, for a moment
+    pub recent: Color,
+    /// background of the revealed cells in the cursor's row and column, when shown
+    pub cross: Color,
+    /// a flag on an empty cell once the game is lost
+    pub wrong_flag: (Color, Color),
+    /// background of the mines that lost the game
+    pub detonated: Color,
+    pub numbers: [Color; 8],
+    /// flag counter color past 90% of the mines, at the mine count and above it
+    pub flag_gradient: [Color; 3],
+    /// text modifier of each digit, on top of its color
+    pub number_modifiers: [Modifier; 8],
+}
+
+impl From<ThemeName> for Theme {
+    fn from(name: ThemeName) -> Self {
+        match name {
+            ThemeName::Default => Self::DEFAULT,
+            ThemeName::Dark => Self::DARK,
+            ThemeName::Light => Self::LIGHT,
+            ThemeName::HighContrast => Self::HIGH_CONTRAST,
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl Theme {
+    pub const DEFAULT: Self = Self {
+        hidden: (Black, Gray),
+        flagged: (Black, LightYellow),
+        flagged_maybe: (Black, Yellow),
+        mine: (Black, LightRed),
+        revealed: Black,
+        cursor: DarkGray,
+        recent: Rgb(40, 40, 90),
+        cross: Rgb(30, 30, 30),
+        wrong_flag: (Red, LightYellow),
+        detonated: Red,
+        numbers: [
+            LightBlue, LightGreen, LightRed, Blue, Red, Cyan, Gray, White,
+        ],
+        flag_gradient: [Yellow, LightYellow, LightRed],
+        number_modifiers: [Modifier::empty(); 8],
+    };
+
+    pub const DARK: Self = Self {
+        hidden: (Gray, DarkGray),
+        flagged: (Black, Yellow),
+        flagged_maybe: (Yellow, DarkGray),
+        mine: (Black, Red),
+        revealed: Black,
+        cursor: Blue,
+        recent: Rgb(40, 40, 60),
+        cross: Rgb(25, 25, 35),
+        wrong_flag: (Red, Yellow),
+        detonated: LightRed,
+        numbers: [
+            LightBlue,
+            LightGreen,
+            LightRed,
+            LightMagenta,
+            Red,
+            Cyan,
+            Gray,
+            DarkGray,
+        ],
+        flag_gradient: [Yellow, LightYellow, LightRed],
+        number_modifiers: [Modifier::empty(); 8],
+    };
+
+    pub const LIGHT: Self = Self {
+        hidden: (DarkGray, Gray),
+        flagged: (Red, Gray),
+        flagged_maybe: (Blue, Gray),
+        mine: (Black, LightRed),
+        revealed: White,
+        cursor: LightCyan,
+        recent: Rgb(255, 255, 170),
+        cross: Rgb(230, 230, 230),
+        wrong_flag: (Black, Rgb(255, 170, 170)),
+        detonated: Red,
+        numbers: [
+            Blue,
+            Green,
+            Red,
+            Rgb(0, 0, 128),
+            Rgb(128, 0, 0),
+            Rgb(0, 128, 128),
+            Black,
+            DarkGray,
+        ],
+        flag_gradient: [Rgb(160, 110, 0), Rgb(200, 80, 0), Red],
+        number_modifiers: [Modifier::empty(); 8],
+    };
+
+    /// Bright glyphs on black for revealed cells, white hidden cells, saturated flags and mines
+    pub const HIGH_CONTRAST: Self = Self {
+        hidden: (Black, White),
+        flagged: (Black, LightYellow),
+        flagged_maybe: (Black, LightCyan),
+        mine: (White, Red),
+        revealed: Black,
+        cursor: Magenta,
+        recent: Blue,
+        cross: DarkGray,
+        wrong_flag: (Red, LightYellow),
+        detonated: LightRed,
+        numbers: [
+            LightCyan,
+            LightGreen,
+            LightRed,
+            LightMagenta,
+            LightYellow,
+            Cyan,
+            White,
+            White,
+        ],
+        flag_gradient: [LightYellow, LightMagenta, LightRed],
+        number_modifiers: [Modifier::BOLD; 8],
+    };
+
+    /// Glyph, foreground, background and modifier a tile is drawn with
+    pub fn tile_style(&self, tile: &Cell) -> (char, Color, Color, Modifier) {
+        match tile.visibility {
+            Hidden(f) => match f {
+                Clear => ('#', self.hidden.0, self.hidden.1, Modifier::empty()),
+                Flagged => ('!', self.flagged.0, self.flagged.1, Modifier::BOLD),
+                FlaggedMaybe => (
+                    '?',
+                    self.flagged_maybe.0,
+                    self.flagged_maybe.1,
+                    Modifier::BOLD,
+                ),
+            },
+            Show => match tile.content {
+                CellContent::Empty(0) => (' ', Reset, self.revealed, Modifier::empty()),
+                CellContent::Empty(n @ 1..=8) => (
+                    std::char::from_digit(n as u32, 10).unwrap(),
+                    self.numbers[n as usize - 1],
+                    self.revealed,
+                    self.number_modifiers[n as usize - 1],
+                ),
+                CellContent::Empty(_) => unreachable!(),
+                CellContent::Mine => ('*', self.mine.0, self.mine.1, Modifier::BOLD),
+            },
+        }
+    }
+
+    /// Like [`Self::tile_style`], for a lost game: wrong flags are crossed out and the mines that
+    /// lost it stand out from the revealed ones
+    pub fn lost_tile_style(&self, tile: &Cell, detonated: bool) -> (char, Color, Color, Modifier) {
+        match (tile.visibility, tile.content) {
+            (Hidden(Flagged), CellContent::Empty(_)) => {
+                ('X', self.wrong_flag.0, self.wrong_flag.1, Modifier::BOLD)
+            }
+            (Show, CellContent::Mine) if detonated => {
+                ('*', self.mine.0, self.detonated, Modifier::BOLD)
+            }
+            _ => self.tile_style(tile),
+        }
+    }
+}
+
+/// Approximate rgb value of a terminal color, `Reset` resolves to `reset`
+pub fn rgb(color: Color, reset: (u8, u8, u8)) -> (u8, u8, u8) {
+    match color {
+        Reset => reset,
+        Black => (0, 0, 0),
+        Red => (170, 0, 0),
+        Green => (0, 170, 0),
+        Yellow => (170, 85, 0),
+        Blue => (0, 0, 170),
+        Magenta => (170, 0, 170),
+        Cyan => (0, 170, 170),
+        Gray => (170, 170, 170),
+        DarkGray => (85, 85, 85),
+        LightRed => (255, 85, 85),
+        LightGreen => (85, 255, 85),
+        LightYellow => (255, 255, 85),
+        LightBlue => (85, 85, 255),
+        LightMagenta => (255, 85, 255),
+        LightCyan => (85, 255, 255),
+        White => (255, 255, 255),
+        Rgb(r, g, b) => (r, g, b),
+        Indexed(_) => reset,
+    }
+}
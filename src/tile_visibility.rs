@@ -1,6 +1,7 @@
 use crate::flag::Flag;
+use serde::{Deserialize, Serialize};
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum TileVisibility {
     Hidden(Flag),
     Show,
@@ -1,39 +1,76 @@
-use crate::action::Action::*;
-use crate::action::DebugAction::*;
-use crate::action::GameCommand::*;
-use crate::action::RestartAction::*;
-use crate::args::MinesweeperArgs;
-use crate::cell_content::CellContent;
-use crate::flag::Flag::*;
-use crate::input_state::InputState;
-use crate::math_util::dist_to_range;
-use crate::minesweeper::{DisplayText, GameState, Minesweeper};
-use crate::tile_visibility::TileVisibility::*;
-use crate::util::Sign::*;
-use crate::win_state::WinState;
+use crate::cast::CastWriter;
+use crate::clipboard::Clipboard;
 use color_eyre::Result;
+use color_eyre::eyre::eyre;
 use crossterm::ExecutableCommand;
+use crossterm::cursor::SetCursorStyle;
 use crossterm::event::{
     self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEventKind,
 };
-use ratatui::buffer::Cell;
-use ratatui::layout::{Position, Rect};
-use ratatui::style::Color::*;
-use ratatui::style::{Color, Modifier};
+use minesweeper::action::Action::*;
+use minesweeper::action::DebugAction::*;
+use minesweeper::action::GameCommand::*;
+use minesweeper::action::RestartAction::*;
+use minesweeper::action::{Action, Cursor, GameCommand};
+use minesweeper::args::{Cli, CursorShape, DEFAULT_DENSITY, MinesweeperArgs, suggested_mines};
+use minesweeper::cell::Cell;
+use minesweeper::cell_content::CellContent::{Empty, Mine};
+use minesweeper::config::{Config, Keymap};
+use minesweeper::diff::Diff::{MultiCell, SingleCell};
+use minesweeper::diff::{Diff, SingleCellDiff};
+use minesweeper::flag::Flag::{Clear, Flagged, FlaggedMaybe};
+use minesweeper::input_state::InputState;
+use minesweeper::math_util::dist_to_range;
+use minesweeper::minesweeper::Change::{Applied, Restarted};
+use minesweeper::minesweeper::{DisplayText, GameState, Minesweeper};
+use minesweeper::paths;
+use minesweeper::spectate;
+use minesweeper::spectate::{Message, Outgoing, Server};
+use minesweeper::stats::Stats;
+use minesweeper::theme::Theme;
+use minesweeper::tile_visibility::TileVisibility::{Hidden, Show};
+use minesweeper::util::Sign::*;
+use minesweeper::util::{DIRS_8, i_xy, valid_neighbors};
+use minesweeper::win_state::WinState;
+use ratatui::backend::{Backend, CrosstermBackend};
+use ratatui::layout::{Constraint, Layout, Position, Rect};
+use ratatui::style::{Modifier, Style};
 use ratatui::{
-    DefaultTerminal, Frame,
+    Frame, Terminal,
     style::Stylize,
-    text::Line,
-    widgets::{Block, Paragraph},
+    text::{Line, Span},
+    widgets::{Block, Clear as ClearWidget, Paragraph, Wrap},
 };
+use std::collections::BTreeSet;
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::time::{Duration, Instant};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+const RECENT_DURATION: Duration = Duration::from_millis(400);
+const INVALID_DURATION: Duration = Duration::from_millis(200);
+const TIME_THRESHOLDS: (Duration, Duration) = (Duration::from_secs(60), Duration::from_secs(180));
+const STATUS_DURATION: Duration = Duration::from_secs(3);
+const LOG_WIDTH: u16 = 28;
+/// Moves the log keeps, older ones are dropped from view
+const LOG_LEN: usize = 200;
 
 struct TerminalGuard;
 
 impl TerminalGuard {
-    fn new() -> Self {
-        std::io::stdout()
-            .execute(event::EnableMouseCapture)
-            .unwrap();
+    fn new(cursor: CursorShape) -> Self {
+        let mut stdout = std::io::stdout();
+        stdout.execute(event::EnableMouseCapture).unwrap();
+        let style = match cursor {
+            CursorShape::Default => None,
+            CursorShape::Solid => Some(SetCursorStyle::SteadyBlock),
+            CursorShape::Blink => Some(SetCursorStyle::BlinkingBlock),
+            CursorShape::Bar => Some(SetCursorStyle::SteadyBar),
+            CursorShape::Underscore => Some(SetCursorStyle::SteadyUnderScore),
+        };
+        if let Some(style) = style {
+            // not every terminal supports cursor styles, a failure here is harmless
+            let _ = stdout.execute(style);
+        }
         Self {}
     }
 }
@@ -43,16 +80,62 @@ impl Drop for TerminalGuard {
         let _ = std::io::stdout()
             .execute(event::DisableMouseCapture)
             .unwrap();
+        let _ = std::io::stdout().execute(SetCursorStyle::DefaultUserShape);
         ratatui::restore();
     }
 }
-pub fn main(args: MinesweeperArgs) -> Result<()> {
-    let _ = TerminalGuard::new();
+/// Plays until the user quits, returning the final state of the game
+pub fn main(cli: &Cli) -> Result<Minesweeper> {
+    let mut args = cli.game;
+    let config = Config::load(cli.config.as_deref())?;
+    config.apply(&mut args);
+    if cli.fit {
+        let (columns, rows) = crossterm::terminal::size()?;
+        // leave room for the border
+        args.width = columns.saturating_sub(2);
+        args.height = rows.saturating_sub(2);
+        // an explicit mine count is kept, the default one wouldn't suit every terminal
+        if !cli.mines_given {
+            args.mines_percent.get_or_insert(DEFAULT_DENSITY);
+        }
+    }
+    let mut app = App::new(args);
+    app.note_puzzle_fallback();
+    if cli.print_seed {
+        eprintln!("seed: {}", app.game.seed);
+        app.seed_banner = true;
+    }
+    // a good puzzle is worth sharing
+    app.show_seed = cli.show_seed || args.puzzle;
+    app.warn_wrong_flags = cli.warn_wrong_flags;
+    app.keymap = config.keymap;
+    app.mouse_only = cli.mouse_only;
+    if let Some((warn, late)) = config.time_thresholds {
+        app.time_thresholds = (Duration::from_secs(warn), Duration::from_secs(late));
+    }
+    if let Some(step) = cli.scroll_step.or(config.scroll_step) {
+        app.scroll_step = step;
+    }
+    app.auto_restart = cli.auto_restart.map(Duration::from_millis);
+    if let Some(addr) = &cli.serve {
+        app.server = Some(Server::bind(addr)?);
+    }
+    if let Some(addr) = &cli.watch {
+        app.watching = Some(spectate::watch(addr)?);
+    }
+
+    let _guard = TerminalGuard::new(args.cursor);
 
     color_eyre::install()?;
     let terminal = ratatui::init();
-    let result = App::new(args).run(terminal);
-    result
+    match &cli.cast {
+        Some(path) => {
+            let size = crossterm::terminal::size()?;
+            let writer = CastWriter::create(std::io::stdout(), path, size)?;
+            app.run(Terminal::new(CrosstermBackend::new(writer))?)
+        }
+        None => app.run(terminal),
+    }
 }
 
 /// The main application which holds the state and logic of the application.
@@ -61,26 +144,160 @@ pub struct App {
     /// Is the application running?
     running: bool,
     viewport_offset: (u16, u16),
+    /// Only scrolling moves the viewport, it doesn't follow the cursor
+    free_scroll: bool,
+    /// Center the viewport on the cursor on the next render
+    recenter: bool,
     game: Minesweeper,
+    /// Are issued actions being recorded into `macro_actions`?
+    recording: bool,
+    macro_actions: Vec<Action>,
+    /// Show the seed on boards that haven't been started
+    seed_banner: bool,
+    /// Show the seed in the title for the whole game
+    show_seed: bool,
+    /// Tint the revealed cells in the cursor's row and column
+    show_cross: bool,
+    /// Describe the cell under the cursor in place of the footer
+    show_inspect: bool,
+    /// Explain the glyphs over the board, shown on the first run until dismissed
+    show_legend: bool,
+    /// Point out misplaced flags as they get opened, in practice and with extra lives
+    warn_wrong_flags: bool,
+    /// Watchers following this game
+    server: Option<Server>,
+    /// Messages from the game being watched, no input is played while set
+    watching: Option<Receiver<Result<Message, String>>>,
+    /// Board cell under the mouse
+    hover: Option<Cursor>,
+    /// Highlight the cells changed by the last move
+    show_recent: bool,
+    /// Cells changed by the last move and when, highlighted until [`RECENT_DURATION`] passes
+    recent: BTreeSet<usize>,
+    recent_at: Option<Instant>,
+    /// When an action last did nothing, the border flashes for [`INVALID_DURATION`]
+    invalid_at: Option<Instant>,
+    /// The counter shows the mines left to flag instead of the flags placed
+    show_remaining: bool,
+    /// Show the move log next to the board
+    show_log: bool,
+    /// Lines the move log is scrolled back by
+    log_scroll: u16,
+    /// Delay before a finished board is replaced by a new one
+    auto_restart: Option<Duration>,
+    /// When the finished board gets replaced, cleared by any key
+    restart_at: Option<Instant>,
+    /// A key was pressed since the board ended, it stays until restarted by hand
+    restart_cancelled: bool,
+    keymap: Keymap,
+    /// Only ctrl-c quits
+    mouse_only: bool,
+    /// Cells the viewport moves per scroll event
+    scroll_step: u16,
+    /// Shown instead of the footer until [`STATUS_DURATION`] passes
+    status_message: Option<(String, Instant)>,
+    /// Wins in a row, from the persisted stats
+    streak: u32,
+    /// The timer turns yellow past the first and red past the second
+    time_thresholds: (Duration, Duration),
+    clipboard: Clipboard,
 }
 impl App {
     /// Construct a new instance of [`App`].
     pub fn new(args: MinesweeperArgs) -> Self {
+        let mut game = Minesweeper::new(args);
+        if !game.args.auto_start {
+            game.input_state.cursor = (game.args.width / 2, game.args.height / 2);
+        }
         Self {
-            game: Minesweeper::new(args),
+            game,
+            recenter: true,
+            show_recent: true,
+            show_legend: !legend_dismissed(),
+            scroll_step: 1,
+            streak: Stats::load().streak,
+            time_thresholds: TIME_THRESHOLDS,
             ..Self::default()
         }
     }
 
     /// Run the application's main loop.
-    pub fn run(mut self, mut terminal: DefaultTerminal) -> Result<()> {
+    pub fn run<B: Backend>(mut self, mut terminal: Terminal<B>) -> Result<Minesweeper> {
         self.running = true;
         while self.running {
             terminal.draw(|frame| self.render(frame))?;
             self.handle_crossterm_events()?;
-            self.game.update();
+            if self.watching.is_some() {
+                self.game.input_state.action = None;
+                self.receive()?;
+                continue;
+            }
+            self.record_action();
+            self.update();
+            self.record_stats();
+            self.schedule_restart();
+        }
+        Ok(self.game)
+    }
+
+    /// Updates the game, forwarding the change to watchers.
+    fn update(&mut self) {
+        let pending = self.game.input_state.action;
+        let change = self.game.update();
+        if pending.is_some() && change.is_none() {
+            self.invalid_at = Some(Instant::now());
+        }
+        if let (Some(Command(Finish)), None, WinState::Ongoing) =
+            (pending, change, self.game.game_state.win_state)
+        {
+            self.set_status("not fully solvable yet");
+        }
+        match change {
+            Some(Restarted) => {
+                self.recenter = true;
+                self.restart_at = None;
+                self.restart_cancelled = false;
+                self.recent.clear();
+                self.note_puzzle_fallback();
+            }
+            Some(Applied) => {
+                self.recent = self
+                    .game
+                    .history
+                    .last_applied()
+                    .unwrap()
+                    .indices()
+                    .into_iter()
+                    .collect();
+                self.recent_at = Some(Instant::now());
+                if self.warn_wrong_flags {
+                    self.warn_wrong_flag();
+                }
+            }
+            _ => {}
+        }
+        let Some(server) = &mut self.server else {
+            return;
+        };
+        server.accept(&self.game);
+        if let Some(message) = change.and_then(|change| Outgoing::from_change(&self.game, change)) {
+            server.broadcast(&message);
+        }
+    }
+
+    /// Mirrors whatever the watched game sent since the last call.
+    fn receive(&mut self) -> Result<()> {
+        let Some(rx) = &self.watching else {
+            return Ok(());
+        };
+        loop {
+            match rx.try_recv() {
+                Ok(Ok(message)) => message.apply_to(&mut self.game),
+                Ok(Err(e)) => return Err(eyre!(e)),
+                Err(TryRecvError::Empty) => return Ok(()),
+                Err(TryRecvError::Disconnected) => return Err(eyre!("connection closed")),
+            }
         }
-        Ok(())
     }
 
     /// Renders the user interface.
@@ -96,6 +313,8 @@ impl App {
                     width,
                     height,
                     mines,
+                    theme: theme_name,
+                    no_cursor_highlight,
                     ..
                 },
             display:
@@ -112,72 +331,200 @@ impl App {
                     win_state,
                     cells: _,
                     flagged_cells,
-                    closed_empty_cells: _,
-                    open_mine_cells: _,
+                    open_mine_cells,
+                    practice,
+                    perfect,
+                    moves,
+                    ..
                 },
             input_state: InputState { cursor: (x, y), .. },
             ..
         } = &self.game;
 
+        let theme = Theme::from(*theme_name);
         let x = x + 1;
         let y = y + 1;
-        let (title, bottom) = match win_state {
-            WinState::Untouched => (
-                Line::from(*title).bold().light_blue().centered(),
-                Line::from(format!("{}x{},{}", width, height, mines)).centered(),
-            ),
-            WinState::Won => (
-                Line::from(*text_top).bold().light_green().centered(),
-                Line::from(*text_bottom).bold().light_green().centered(),
-            ),
+        // the footer as (priority, text), the higher numbers are dropped first when it doesn't fit
+        let (title, mut footer): (Line, Vec<(u8, Span)>) = match win_state {
+            WinState::Untouched | WinState::Revealed => {
+                let percent = *mines as f32 * 100.0 / (*width as f32 * *height as f32);
+                let mut footer = vec![
+                    (3, Span::raw(format!("{}x{},", width, height))),
+                    (0, Span::raw(mines.to_string())),
+                    (1, Span::raw(format!(" ({:.0}%)", percent))),
+                ];
+                if self.seed_banner {
+                    footer.push((4, Span::raw(format!(" seed {}", self.game.seed))));
+                }
+                let suggested = suggested_mines(*width, *height);
+                if suggested != *mines {
+                    footer.push((7, format!(" suggested: {suggested}").dark_gray()));
+                }
+                (Line::from(*title).bold().light_blue(), footer)
+            }
+            WinState::Won => {
+                let mut top = Line::from(*text_top).bold().light_green();
+                if *perfect {
+                    top.push_span(" PERFECT".light_yellow());
+                }
+                let mut footer = vec![(0, text_bottom.bold().light_green())];
+                for (label, time) in &self.game.splits {
+                    let split = format!(" {label}:{:.1}s", time.as_secs_f32());
+                    footer.push((5, split.light_cyan()));
+                }
+                (top, footer)
+            }
             WinState::Lost => (
-                Line::from(*text_top).bold().light_red().centered(),
-                Line::from(*text_bottom).bold().light_red().centered(),
+                Line::from(*text_top).bold().light_red(),
+                vec![(0, text_bottom.bold().light_red())],
             ),
             _ => {
-                let mut stats = format!(
-                    "{:mines_digits$}/{} ({:width_digits$},{:height_digits$}) {}x{}",
-                    flagged_cells, mines, x, y, width, height
-                );
-                if stats.len() as u16 > *width {
-                    stats = format!("{} {},{}", mines - flagged_cells, x, y);
-                }
-
-                (
-                    Line::from(*title).bold().light_blue().centered(),
-                    Line::from(stats).centered(),
-                )
+                // signed, there can be more flags than mines
+                let remaining = *mines as i64 - *flagged_cells as i64;
+                let counter = if self.show_remaining {
+                    remaining
+                } else {
+                    *flagged_cells as i64
+                };
+                let counter_style = flag_count_style(*flagged_cells, *mines, &theme);
+                let stats = vec![
+                    (
+                        0,
+                        Span::styled(format!("{:mines_digits$}", counter), counter_style),
+                    ),
+                    (2, Span::raw(format!("/{}", mines))),
+                    (
+                        1,
+                        Span::raw(format!(" ({:width_digits$},{:height_digits$})", x, y)),
+                    ),
+                    (6, Span::raw(format!(" {}x{}", width, height))),
+                    (4, Span::raw(format!(" #{}", moves))),
+                ];
+                (Line::from(*title).bold().light_blue(), stats)
             }
         };
-        let area = frame.area().clamp(Rect::new(0, 0, width + 2, height + 2));
+        let mut title = title.centered();
+        if self.show_seed {
+            title.push_span(format!(" seed {}", self.game.seed).dark_gray());
+        }
+        if self.recording {
+            title.push_span(" REC".light_red());
+        }
+        if let (WinState::Untouched | WinState::Won | WinState::Lost, 1..) =
+            (win_state, self.streak)
+        {
+            footer.push((6, Span::raw(format!(" streak {}", self.streak))));
+        }
+        if let WinState::Ongoing | WinState::Won | WinState::Lost = win_state {
+            let elapsed = self.game.elapsed();
+            let time = format!(" {}s", elapsed.as_secs());
+            let style = time_style(elapsed, self.time_thresholds);
+            footer.push((3, Span::styled(time, style)));
+            let penalty = self.game.penalty.as_secs();
+            if penalty > 0 {
+                footer.push((3, format!(" +{penalty}s").light_red()));
+            }
+        }
+        if self.show_inspect {
+            footer = vec![(0, Span::raw(self.inspect()))];
+        }
+        if self
+            .status_message
+            .as_ref()
+            .is_some_and(|(_, at)| at.elapsed() > STATUS_DURATION)
+        {
+            self.status_message = None;
+        }
+        if let Some((message, _)) = &self.status_message {
+            footer = vec![(0, message.clone().yellow())];
+        }
+        if self.game.game_state.lives > 1 && !practice {
+            let lives = self.game.game_state.lives_left();
+            footer.push((1, format!(" {lives}\u{2665}").light_red()));
+        }
+        if let (WinState::Ongoing | WinState::Won, true) =
+            (win_state, self.game.game_state.nf_valid())
+        {
+            footer.push((2, " NF".light_green()));
+        }
+        if *practice && *open_mine_cells > 0 {
+            footer.push((1, format!(" x{open_mine_cells}").light_red()));
+        }
+        if self.free_scroll {
+            footer.push((5, " free".dark_gray()));
+        }
+        let mut board_area = frame.area();
+        if self.show_log {
+            let [board, log] =
+                Layout::horizontal([Constraint::Min(0), Constraint::Length(LOG_WIDTH)])
+                    .areas(board_area);
+            board_area = board;
+            self.render_log(frame, log);
+        }
+        let area = board_area.clamp(Rect::new(0, 0, width + 2, height + 2));
 
-        frame.render_widget(
-            Paragraph::new("")
-                .block(Block::bordered().title(title).title_bottom(bottom))
+        // big boards scroll, so they only need as much room as the smallest one
+        let min_size = self.game.args.min_size;
+        let needed_w = (width + 2).min(min_size + 2);
+        let needed_h = (height + 2).min(min_size + 2);
+        if area.width < needed_w || area.height < needed_h {
+            frame.render_widget(
+                Paragraph::new(format!(
+                    "Please enlarge your terminal to at least {needed_w}x{needed_h}"
+                ))
+                .wrap(Wrap { trim: true })
                 .centered(),
-            area,
-        );
-
-        if area.height == 0 && area.width == 0 {
+                frame.area(),
+            );
             return;
         }
 
+        let bottom = fit_footer(footer, area.width.saturating_sub(2) as usize).centered();
+        let mut block = Block::bordered().title(title).title_bottom(bottom);
+        if self
+            .invalid_at
+            .is_some_and(|at| at.elapsed() <= INVALID_DURATION)
+        {
+            block = block.border_style(Style::new().red());
+        }
+        frame.render_widget(Paragraph::new("").block(block).centered(), area);
+
         let (vox, voy) = &mut self.viewport_offset;
 
+        if self.recenter {
+            self.recenter = false;
+            *vox = (x - 1).saturating_sub((area.width - 2) / 2);
+            *voy = (y - 1).saturating_sub((area.height - 2) / 2);
+        }
+
         let i0 = area.x + 1;
         let i1 = area.x + area.width - 1;
-        let x_offset = dist_to_range(x as i16 - *vox as i16, i0 as i16, i1 as i16 - 1);
+        let x_offset = if self.free_scroll {
+            0
+        } else {
+            dist_to_range(x as i16 - *vox as i16, i0 as i16, i1 as i16 - 1)
+        };
         *vox = vox
             .saturating_add_signed(x_offset)
             .min(width.saturating_sub(area.width.saturating_sub(2)));
 
         let j0 = area.y + 1;
         let j1 = area.y + area.height - 1;
-        let y_offset = dist_to_range(y as i16 - *voy as i16, j0 as i16, j1 as i16 - 1);
+        let y_offset = if self.free_scroll {
+            0
+        } else {
+            dist_to_range(y as i16 - *voy as i16, j0 as i16, j1 as i16 - 1)
+        };
         *voy = voy
             .saturating_add_signed(y_offset)
             .min(height.saturating_sub(area.height.saturating_sub(2)));
 
+        if self
+            .recent_at
+            .is_some_and(|at| at.elapsed() > RECENT_DURATION)
+        {
+            self.recent.clear();
+        }
         for j_screen in j0..j1 {
             let j_game = (j_screen - 1).saturating_add(*voy);
             for i_screen in i0..i1 {
@@ -187,115 +534,264 @@ impl App {
                     continue;
                 };
 
-                const HIDDEN_COLOR: Color = Gray;
-                const WARN_COLOR: Color = LightYellow;
-                const CLEAR_COLOR: Color = Black;
-
-                let (char, fg, bg, modifier) = match tile.visibility {
-                    Hidden(f) => match f {
-                        Clear => ('#', Black, HIDDEN_COLOR, Modifier::empty()),
-                        Flagged => ('!', Black, WARN_COLOR, Modifier::BOLD),
-                        FlaggedMaybe => ('?', Black, Yellow, Modifier::BOLD),
-                    },
-                    Show => match tile.content {
-                        CellContent::Empty(n) => match n {
-                            0 => (' ', Reset, CLEAR_COLOR, Modifier::empty()),
-                            1 => ('1', LightBlue, CLEAR_COLOR, Modifier::empty()),
-                            2 => ('2', LightGreen, CLEAR_COLOR, Modifier::empty()),
-                            3 => ('3', LightRed, CLEAR_COLOR, Modifier::empty()),
-                            4 => ('4', Blue, CLEAR_COLOR, Modifier::empty()),
-                            5 => ('5', Red, CLEAR_COLOR, Modifier::empty()),
-                            6 => ('6', Cyan, CLEAR_COLOR, Modifier::empty()),
-                            7 => ('7', Gray, CLEAR_COLOR, Modifier::empty()),
-                            8 => ('8', White, CLEAR_COLOR, Modifier::empty()),
-                            _ => unreachable!(),
-                        },
-                        CellContent::Mine => ('*', Black, LightRed, Modifier::BOLD),
-                    },
+                let (char, fg, mut bg, mut modifier) = if let WinState::Lost = win_state {
+                    let index = j_game as usize * *width as usize + i_game as usize;
+                    theme.lost_tile_style(tile, self.game.detonated.contains(&index))
+                } else {
+                    theme.tile_style(tile)
                 };
+                if self.show_cross
+                    && tile.visibility == Show
+                    && (i_game == x - 1 || j_game == y - 1)
+                {
+                    bg = theme.cross;
+                }
+                if self.show_recent
+                    && self
+                        .recent
+                        .contains(&(j_game as usize * *width as usize + i_game as usize))
+                {
+                    bg = theme.recent;
+                }
+                if !no_cursor_highlight && (i_game, j_game) == (x - 1, y - 1) {
+                    bg = theme.cursor;
+                }
+                if self.hover == Some((i_game, j_game)) {
+                    modifier |= Modifier::UNDERLINED;
+                }
 
-                let w = frame.area().width;
-                let mut c = Cell::new("");
+                let Some(c) = frame.buffer_mut().cell_mut((i_screen, j_screen)) else {
+                    continue;
+                };
+                c.reset();
                 c.set_char(char).set_fg(fg).set_bg(bg);
                 c.modifier = modifier;
-                frame.buffer_mut().content[w as usize * j_screen as usize + i_screen as usize] = c;
             }
         }
-        let x = x.saturating_sub(*vox);
-        let y = y.saturating_sub(*voy);
-        frame.set_cursor_position(Position { x, y });
+        if self.show_legend {
+            render_legend(frame, &theme);
+        }
+        // while free scrolling the cursor may be out of view, the terminal cursor stays hidden then
+        let (Some(x), Some(y)) = (x.checked_sub(*vox), y.checked_sub(*voy)) else {
+            return;
+        };
+        if (i0..i1).contains(&x) && (j0..j1).contains(&y) {
+            frame.set_cursor_position(Position { x, y });
+        }
+    }
+
+    /// Lists the applied moves, newest first.
+    fn render_log(&self, frame: &mut Frame, area: Rect) {
+        let MinesweeperArgs { width, height, .. } = self.game.args;
+        let lines: Vec<Line> = self
+            .game
+            .history
+            .applied()
+            .iter()
+            .rev()
+            .take(LOG_LEN)
+            .map(|diff| Line::from(describe(diff, width, height)))
+            .collect();
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(Block::bordered().title(Line::from("moves").centered()))
+                .scroll((self.log_scroll, 0)),
+            area,
+        );
     }
 
     fn handle_crossterm_events(&mut self) -> Result<()> {
+        // time out regularly so anything not driven by input still gets drawn
+        if !event::poll(POLL_INTERVAL)? {
+            return Ok(());
+        }
         match event::read()? {
             // it's important to check KeyEventKind::Press to avoid handling key release events
-            Event::Key(key) if key.kind == KeyEventKind::Press => self.on_key_event(key),
+            Event::Key(key) if key.kind == KeyEventKind::Press => {
+                if self.restart_at.take().is_some() {
+                    self.restart_cancelled = true;
+                    self.set_status("auto restart cancelled");
+                }
+                self.on_key_event(key)
+            }
             Event::Mouse(m)
                 if m.kind == MouseEventKind::ScrollRight
                     || (m.kind == MouseEventKind::ScrollDown
                         && m.modifiers.contains(KeyModifiers::ALT)) =>
             {
-                self.viewport_offset.0 = self.viewport_offset.0.saturating_add(1);
+                self.viewport_offset.0 = self.viewport_offset.0.saturating_add(self.scroll_step);
             }
             Event::Mouse(m)
                 if m.kind == MouseEventKind::ScrollLeft
                     || (m.kind == MouseEventKind::ScrollUp
                         && m.modifiers.contains(KeyModifiers::ALT)) =>
             {
-                self.viewport_offset.0 = self.viewport_offset.0.saturating_sub(1);
+                self.viewport_offset.0 = self.viewport_offset.0.saturating_sub(self.scroll_step);
             }
             Event::Mouse(m) if m.kind == MouseEventKind::ScrollDown => {
-                self.viewport_offset.1 = self.viewport_offset.1.saturating_add(1);
+                self.viewport_offset.1 = self.viewport_offset.1.saturating_add(self.scroll_step);
             }
             Event::Mouse(m) if m.kind == MouseEventKind::ScrollUp => {
-                self.viewport_offset.1 = self.viewport_offset.1.saturating_sub(1);
-            }
-            Event::Mouse(m) => match m.kind {
-                MouseEventKind::Down(button) => 'block: {
-                    if !(1..self.game.args.width + 1).contains(&m.column)
-                        || !(1..self.game.args.height + 1).contains(&m.row)
-                    {
-                        break 'block;
-                    }
-                    self.game.input_state.cursor = (
-                        m.column - 1 + self.viewport_offset.0,
-                        m.row - 1 + self.viewport_offset.1,
-                    );
-                    let cursor = self.game.input_state.cursor;
+                self.viewport_offset.1 = self.viewport_offset.1.saturating_sub(self.scroll_step);
+            }
+            Event::Mouse(m) if m.kind == MouseEventKind::Moved => {
+                self.hover = self.board_cell(m.column, m.row);
+            }
+            Event::Mouse(m) => {
+                if let MouseEventKind::Down(button) = m.kind {
+                    let Some(cursor) = self.board_cell(m.column, m.row) else {
+                        return Ok(());
+                    };
+                    self.game.input_state.cursor = cursor;
                     match button {
                         MouseButton::Left => {
-                            self.game.input_state.action = Some(Command(OpenCell(cursor)))
+                            self.game.input_state.action = Some(Command(self.open_or_chord(cursor)))
                         }
                         MouseButton::Right | MouseButton::Middle => {
                             self.game.input_state.action = Some(Command(FlagCell(cursor)))
                         }
                     };
                 }
-                _ => {}
-            },
-            Event::Resize(_, _) => {}
+            }
+            Event::Resize(columns, rows) => self.clamp_viewport(columns, rows),
             _ => {}
         }
         Ok(())
     }
 
+    /// The board cell drawn at a terminal position, `None` on the border or past the board.
+    fn board_cell(&self, column: u16, row: u16) -> Option<Cursor> {
+        let (vox, voy) = self.viewport_offset;
+        let x = column.checked_sub(1)? + vox;
+        let y = row.checked_sub(1)? + voy;
+        (x < self.game.args.width && y < self.game.args.height).then_some((x, y))
+    }
+
+    /// Keeps the viewport from scrolling past the board after the terminal is resized.
+    fn clamp_viewport(&mut self, columns: u16, rows: u16) {
+        let MinesweeperArgs { width, height, .. } = self.game.args;
+        let visible_w = columns.min(width + 2).saturating_sub(2);
+        let visible_h = rows.min(height + 2).saturating_sub(2);
+        let (vox, voy) = &mut self.viewport_offset;
+        *vox = (*vox).min(width.saturating_sub(visible_w));
+        *voy = (*voy).min(height.saturating_sub(visible_h));
+    }
+
     /// Handles the key events and updates the state of [`App`].
     fn on_key_event(&mut self, key: KeyEvent) {
         let cursor = self.game.input_state.cursor;
 
+        // the watched game is a read only copy, changing it would only make it drift from the host
+        if self.watching.is_some() && !is_watch_key(&key) {
+            return;
+        }
+
+        // remapped keys come first, so a key never does two things
+        if self.keymap.undo.iter().any(|binding| binding.matches(&key)) {
+            self.game.input_state.action = Some(Debug(Undo));
+            return;
+        }
+        if self.keymap.redo.iter().any(|binding| binding.matches(&key)) {
+            self.game.input_state.action = Some(Debug(Redo));
+            return;
+        }
+
+        if let (KeyModifiers::CONTROL, KeyCode::Char('c' | 'C')) = (key.modifiers, key.code) {
+            return self.quit();
+        }
+        // the keys below are pressed alone or with shift, see BUILTIN_KEYS
+        if key
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+        {
+            return;
+        }
+
         match (key.modifiers, key.code) {
-            (KeyModifiers::CONTROL, KeyCode::Char('z') | KeyCode::Char('Z')) => {
-                self.game.input_state.action = Some(Debug(Undo))
-            }
-            (KeyModifiers::CONTROL, KeyCode::Char('y') | KeyCode::Char('Y')) => {
-                self.game.input_state.action = Some(Debug(Redo))
-            }
-            (_, KeyCode::Esc | KeyCode::Char('q'))
-            | (KeyModifiers::CONTROL, KeyCode::Char('c') | KeyCode::Char('C')) => self.quit(),
+            // a stray key shouldn't end a game played with the mouse
+            (_, KeyCode::Esc | KeyCode::Char('q')) if !self.mouse_only => self.quit(),
             // Add other key handlers here.
             (_, KeyCode::Char('k')) => {
                 self.game.input_state.action = Some(Command(Surrender));
             }
+            (_, KeyCode::Char('a')) => {
+                self.game.input_state.action = Some(Command(AutoResolve));
+            }
+            (_, KeyCode::Char('A')) => {
+                self.game.input_state.action = Some(Command(Finish));
+            }
+            (_, KeyCode::Char('v')) => {
+                self.free_scroll = !self.free_scroll;
+            }
+            (_, KeyCode::Char('c')) => {
+                self.show_remaining = !self.show_remaining;
+            }
+            (_, KeyCode::Char('l')) => {
+                self.show_log = !self.show_log;
+            }
+            (_, KeyCode::PageDown) => {
+                let moves = self.game.history.applied().len().min(LOG_LEN);
+                self.log_scroll = (self.log_scroll + 1).min(moves.saturating_sub(1) as u16);
+            }
+            (_, KeyCode::PageUp) => {
+                self.log_scroll = self.log_scroll.saturating_sub(1);
+            }
+            (_, KeyCode::Char('h')) => {
+                self.show_recent = !self.show_recent;
+                self.set_status(if self.show_recent {
+                    "highlighting changes"
+                } else {
+                    "not highlighting changes"
+                });
+            }
+            (_, KeyCode::Char('m')) => {
+                self.recording = true;
+                self.macro_actions.clear();
+            }
+            (_, KeyCode::Char('M')) => {
+                self.recording = false;
+            }
+            (_, KeyCode::Char('@')) => {
+                self.recording = false;
+                self.play_macro();
+            }
+            (_, KeyCode::Char('L')) => {
+                self.game.args.lock_aspect = !self.game.args.lock_aspect;
+                self.set_status(if self.game.args.lock_aspect {
+                    "resizing keeps the proportions"
+                } else {
+                    "resizing one side at a time"
+                });
+            }
+            (_, KeyCode::Char('D')) => {
+                self.game.args.keep_density = !self.game.args.keep_density;
+                self.set_status(if self.game.args.keep_density {
+                    "resizing keeps the density"
+                } else {
+                    "resizing keeps the mine count"
+                });
+            }
+            (_, KeyCode::Char('y')) => self.copy_seed(),
+            (_, KeyCode::Char('Y')) => self.paste_seed(),
+            (_, KeyCode::Char('s')) => {
+                let label = (self.game.splits.len() + 1).to_string();
+                if self.game.split(label) {
+                    let time = self.game.elapsed().as_secs_f32();
+                    self.set_status(format!("split {} at {time:.1}s", self.game.splits.len()));
+                }
+            }
+            (_, KeyCode::Char('g')) => {
+                self.show_cross = !self.show_cross;
+            }
+            (_, KeyCode::Char('?')) => {
+                self.show_legend = !self.show_legend;
+                if !self.show_legend {
+                    dismiss_legend();
+                }
+            }
+            (_, KeyCode::Char('i')) => {
+                self.show_inspect = !self.show_inspect;
+            }
             (_, KeyCode::Char('r')) => {
                 self.game.input_state.action = Some(Restart(None));
             }
@@ -306,7 +802,10 @@ impl App {
                 self.game.input_state.action = Some(Restart(Some(IncrementMinesPercent(Negative))));
             }
             (_, KeyCode::Char('x' | ' ')) => {
-                self.game.input_state.action = Some(Command(OpenCell(cursor)));
+                self.game.input_state.action = Some(Command(self.open_or_chord(cursor)));
+            }
+            (_, KeyCode::Char('F')) => {
+                self.game.input_state.action = Some(Command(FlagNeighbors(cursor)));
             }
             (_, KeyCode::Char('z' | 'f')) => {
                 self.game.input_state.action = Some(Command(FlagCell(cursor)));
@@ -314,6 +813,13 @@ impl App {
             (_, KeyCode::Backspace) => {
                 self.game.input_state.action = Some(Command(ClearFlag(cursor)));
             }
+            (_, KeyCode::Char('b'))
+                if matches!(self.game.game_state.win_state, WinState::Untouched) =>
+            {
+                let MinesweeperArgs { width, height, .. } = self.game.args;
+                let mines = suggested_mines(width, height);
+                self.game.input_state.action = Some(Restart(Some(SetMines(mines))));
+            }
             (_, KeyCode::Char('+')) => {
                 self.game.input_state.action = Some(Restart(Some(IncrementMines(Positive))));
             }
@@ -321,9 +827,7 @@ impl App {
                 self.game.input_state.action = Some(Restart(Some(IncrementMines(Negative))));
             }
             (modifiers, KeyCode::Right) => {
-                if modifiers.contains(KeyModifiers::CONTROL) {
-                    self.game.input_state.action = Some(Debug(Redo))
-                } else if modifiers.contains(KeyModifiers::SHIFT) {
+                if modifiers.contains(KeyModifiers::SHIFT) {
                     self.game.input_state.action = Some(Restart(Some(ResizeH(Positive))))
                 } else {
                     self.game.move_cursor(1, 0)
@@ -337,9 +841,7 @@ impl App {
                 }
             }
             (modifiers, KeyCode::Left) => {
-                if modifiers.contains(KeyModifiers::CONTROL) {
-                    self.game.input_state.action = Some(Debug(Undo))
-                } else if modifiers.contains(KeyModifiers::SHIFT) {
+                if modifiers.contains(KeyModifiers::SHIFT) {
                     self.game.input_state.action = Some(Restart(Some(ResizeH(Negative))))
                 } else {
                     self.game.move_cursor(-1, 0)
@@ -356,8 +858,661 @@ impl App {
         }
     }
 
+    /// Opening an already open number chords it instead.
+    fn open_or_chord(&self, (x, y): Cursor) -> GameCommand {
+        match self.game.get_tile(x, y) {
+            Some(tile) if tile.visibility == Show => Chord((x, y)),
+            _ => OpenCell((x, y)),
+        }
+    }
+
+    /// Adds the pending action to the macro while recording, a restart starts the macro over.
+    fn record_action(&mut self) {
+        let (true, Some(action)) = (self.recording, self.game.input_state.action) else {
+            return;
+        };
+        match action {
+            Restart(_) => self.macro_actions.clear(),
+            _ => self.macro_actions.push(action),
+        }
+    }
+
+    /// Replays the recorded actions against the current game.
+    fn play_macro(&mut self) {
+        for i in 0..self.macro_actions.len() {
+            self.game.input_state.action = Some(self.macro_actions[i]);
+            self.update();
+        }
+        self.set_status(format!("replayed {} actions", self.macro_actions.len()));
+    }
+
+    /// Says so when `--puzzle` had to settle for an ordinary board
+    fn note_puzzle_fallback(&mut self) {
+        if self.game.puzzle_fallback {
+            self.set_status("no puzzle found at this density, this board may need guessing");
+        }
+    }
+
+    /// Shows a message in the footer for a few seconds.
+    fn set_status(&mut self, message: impl Into<String>) {
+        self.status_message = Some((message.into(), Instant::now()));
+    }
+
+    /// In games that go on after a mistake, points out a flag on an empty cell the last move opened,
+    /// by a flood, the solver or a reveal. Placing a wrong flag says nothing, that would give it away.
+    fn warn_wrong_flag(&mut self) {
+        let GameState {
+            practice, lives, ..
+        } = self.game.game_state;
+        if !practice && lives <= 1 {
+            return;
+        }
+        let Some(diff) = self.game.history.last_applied() else {
+            return;
+        };
+        let wrong = diff.opened_wrong_flags();
+        let MinesweeperArgs { width, height, .. } = self.game.args;
+        let Some((x, y)) = wrong.first().and_then(|&i| i_xy(i, width, height)) else {
+            return;
+        };
+        let mut message = format!("wrong flag at {},{}", x + 1, y + 1);
+        if wrong.len() > 1 {
+            message.push_str(&format!(" (+{} more)", wrong.len() - 1));
+        }
+        self.set_status(message);
+    }
+
+    /// Describes the cell under the cursor and the flags and hidden cells around it
+    fn inspect(&self) -> String {
+        let (x, y) = self.game.input_state.cursor;
+        let MinesweeperArgs { width, height, .. } = self.game.args;
+        let Some(tile) = self.game.get_tile(x, y) else {
+            return String::new();
+        };
+        let state = match (tile.visibility, tile.content) {
+            (Hidden(Clear), _) => "hidden".to_string(),
+            (Hidden(Flagged), _) => "flagged".to_string(),
+            (Hidden(FlaggedMaybe), _) => "maybe".to_string(),
+            (Show, Empty(0)) => "empty".to_string(),
+            (Show, Empty(n)) => format!("shows {n}"),
+            (Show, Mine) => "mine".to_string(),
+        };
+        let (mut flags, mut hidden) = (0, 0);
+        for (i, j) in valid_neighbors(&DIRS_8, (x, y), width, height) {
+            match self.game.get_tile(i, j).map(|cell| cell.visibility) {
+                Some(Hidden(Flagged)) => flags += 1,
+                Some(Hidden(_)) => hidden += 1,
+                _ => {}
+            }
+        }
+        format!(
+            "{},{} {state}, {flags} flags {hidden} hidden around",
+            x + 1,
+            y + 1
+        )
+    }
+
+    /// Copies the board as `seed,WxH,mines`, enough to play it again
+    fn copy_seed(&mut self) {
+        let MinesweeperArgs {
+            width,
+            height,
+            mines,
+            ..
+        } = self.game.args;
+        let text = format!("{},{}x{},{}", self.game.seed, width, height, mines);
+        match self.clipboard.set_text(text) {
+            Ok(()) => self.set_status("seed copied"),
+            Err(e) => self.set_status(format!("can't copy the seed: {e}")),
+        }
+    }
+
+    /// Restarts with the seed on the clipboard, either alone or as `seed,WxH,mines`
+    fn paste_seed(&mut self) {
+        let text = match self.clipboard.get_text() {
+            Ok(text) => text,
+            Err(e) => return self.set_status(format!("can't paste a seed: {e}")),
+        };
+        let Some(args) = parse_seed(text.trim(), self.game.args) else {
+            return self.set_status("no seed on the clipboard");
+        };
+        if let Err(e) = args.validate() {
+            return self.set_status(format!("can't play that board: {e}"));
+        }
+        self.game.args = args;
+        self.game.input_state.action = Some(Restart(None));
+        self.set_status("seed pasted");
+    }
+
+    /// Restarts a finished board once the auto restart delay is over.
+    fn schedule_restart(&mut self) {
+        let Some(delay) = self.auto_restart else {
+            return;
+        };
+        let (WinState::Won | WinState::Lost) = self.game.game_state.win_state else {
+            // undoing the last move takes the board out of the finished state
+            self.restart_at = None;
+            return;
+        };
+        if self.restart_cancelled {
+            return;
+        }
+        let restart_at = *self
+            .restart_at
+            .get_or_insert_with(|| Instant::now() + delay);
+        if Instant::now() >= restart_at {
+            self.game.input_state.action = Some(Restart(None));
+            self.update();
+        }
+    }
+
+    /// Persist the result the first time the current board ends, zen and
+    /// practice games aren't counted.
+    fn record_stats(&mut self) {
+        let (WinState::Won | WinState::Lost) = self.game.game_state.win_state else {
+            return;
+        };
+        if self.game.stats_recorded || self.game.args.zen || self.game.args.practice {
+            return;
+        }
+        self.game.stats_recorded = true;
+        let win_state = self.game.game_state.win_state;
+        if let Ok(stats) = Stats::record(win_state, self.game.game_state.nf_valid()) {
+            self.streak = stats.streak;
+        }
+    }
+
     /// Set running to false to quit the application.
     fn quit(&mut self) {
         self.running = false;
     }
 }
+
+/// A move in words, coordinates are 1 based like the footer
+fn describe(diff: &Diff, w: u16, h: u16) -> String {
+    let xy = |index| {
+        let (x, y) = i_xy(index, w, h).unwrap();
+        format!("({},{})", x + 1, y + 1)
+    };
+    match diff {
+        SingleCell(SingleCellDiff { index, after, .. }) => {
+            match (after.visibility, after.content) {
+                (Show, Mine) => format!("opened {} -> mine", xy(*index)),
+                (Show, Empty(n)) => format!("opened {} -> {n}", xy(*index)),
+                (Hidden(Flagged), _) => format!("flagged {}", xy(*index)),
+                (Hidden(FlaggedMaybe), _) => format!("marked {}", xy(*index)),
+                (Hidden(Clear), _) => format!("cleared {}", xy(*index)),
+            }
+        }
+        MultiCell(diffs) => {
+            let opened = diffs
+                .iter()
+                .filter(|diff| diff.after.visibility == Show)
+                .count();
+            let flagged = diffs.len() - opened;
+            let at = diffs.first().map(|diff| xy(diff.index)).unwrap_or_default();
+            match (opened, flagged) {
+                (_, 0) => format!("opened {opened} cells at {at}"),
+                (0, _) => format!("flagged {flagged} cells"),
+                _ => format!("opened {opened}, flagged {flagged}"),
+            }
+        }
+    }
+}
+
+/// Green while there's time, then yellow and red as the thresholds pass
+fn time_style(elapsed: Duration, (warn, late): (Duration, Duration)) -> Style {
+    match elapsed {
+        t if t < warn => Style::new().light_green(),
+        t if t < late => Style::new().light_yellow(),
+        _ => Style::new().light_red(),
+    }
+}
+
+/// `args` with the seed, and size and mines if given, from `seed` or `seed,WxH,mines`
+fn parse_seed(text: &str, mut args: MinesweeperArgs) -> Option<MinesweeperArgs> {
+    let mut parts = text.split(',').map(str::trim);
+    args.seed = Some(parts.next()?.parse().ok()?);
+    if let Some(size) = parts.next() {
+        let (width, height) = size.split_once('x')?;
+        args.width = width.parse().ok()?;
+        args.height = height.parse().ok()?;
+        args.mines = parts.next()?.parse().ok()?;
+        args.mines_percent = None;
+    }
+    parts.next().is_none().then_some(args)
+}
+
+/// Plain while far from the mine count, then the theme's gradient as the flags near, reach and pass it
+fn flag_count_style(flagged: u32, mines: u32, theme: &Theme) -> Style {
+    let [near, reached, over] = theme.flag_gradient;
+    if flagged == 0 {
+        Style::new()
+    } else if flagged > mines {
+        Style::new().fg(over)
+    } else if flagged == mines {
+        Style::new().fg(reached)
+    } else if flagged as u64 * 10 >= mines as u64 * 9 {
+        Style::new().fg(near)
+    } else {
+        Style::new()
+    }
+}
+
+/// Keeps the footer items in order, dropping the least important ones until they fit in `width`
+fn fit_footer(mut items: Vec<(u8, Span)>, width: usize) -> Line {
+    while items.len() > 1 && items.iter().map(|(_, span)| span.width()).sum::<usize>() > width {
+        let (drop, _) = items
+            .iter()
+            .enumerate()
+            .max_by_key(|(i, (priority, _))| (*priority, *i))
+            .unwrap();
+        items.remove(drop);
+    }
+    Line::from_iter(items.into_iter().map(|(_, span)| span))
+}
+
+/// The empty file whose presence means the player has closed the legend before
+fn legend_path() -> Option<std::path::PathBuf> {
+    Some(paths::data_dir()?.join("legend_dismissed"))
+}
+
+fn legend_dismissed() -> bool {
+    legend_path().is_some_and(|path| path.exists())
+}
+
+/// Marks the legend as seen by the player, so it doesn't open on its own anymore
+fn dismiss_legend() {
+    let Some(path) = legend_path() else {
+        return;
+    };
+    // at worst the legend shows up again next time
+    let _ = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|()| std::fs::write(path, ""));
+}
+
+/// What each glyph means, drawn with the same glyphs and colors as the board
+/// Keys that still work while watching: quitting, moving around and the display toggles
+fn is_watch_key(key: &KeyEvent) -> bool {
+    match (key.modifiers, key.code) {
+        (KeyModifiers::CONTROL, KeyCode::Char('c' | 'C')) => true,
+        (modifiers, KeyCode::Left | KeyCode::Right | KeyCode::Up | KeyCode::Down) => {
+            !modifiers.intersects(KeyModifiers::SHIFT | KeyModifiers::CONTROL)
+        }
+        (_, KeyCode::Esc | KeyCode::PageUp | KeyCode::PageDown) => true,
+        (_, KeyCode::Char('q' | 'v' | 'c' | 'l' | 'h' | 'g' | '?' | 'i')) => true,
+        _ => false,
+    }
+}
+
+fn render_legend(frame: &mut Frame, theme: &Theme) {
+    let cell = |visibility, content| Cell {
+        visibility,
+        content,
+    };
+    let entries = [
+        (cell(Hidden(Clear), Empty(0)), "hidden"),
+        (cell(Hidden(Flagged), Empty(0)), "flag"),
+        (cell(Hidden(FlaggedMaybe), Empty(0)), "maybe a mine"),
+        (cell(Show, Mine), "mine"),
+        (cell(Show, Empty(1)), "mines around it"),
+        (cell(Show, Empty(0)), "no mines around"),
+    ];
+    let lost = [
+        (cell(Hidden(Flagged), Empty(0)), false, "wrong flag, lost"),
+        (cell(Show, Mine), true, "mine that lost it"),
+    ];
+    let entries =
+        entries
+            .into_iter()
+            .map(|(tile, meaning)| (theme.tile_style(&tile), meaning))
+            .chain(lost.into_iter().map(|(tile, detonated, meaning)| {
+                (theme.lost_tile_style(&tile, detonated), meaning)
+            }));
+    let mut lines: Vec<Line> = entries
+        .map(|((char, fg, bg, modifier), meaning)| {
+            let glyph = Style::new().fg(fg).bg(bg).add_modifier(modifier);
+            Line::from(vec![
+                Span::styled(char.to_string(), glyph),
+                Span::raw(format!(" {meaning}")),
+            ])
+        })
+        .collect();
+    lines.push(Line::from("? closes this".dark_gray()));
+
+    let area = frame.area();
+    let (w, h) = (
+        22.min(area.width),
+        (lines.len() as u16 + 2).min(area.height),
+    );
+    let rect = Rect::new(
+        area.x + (area.width - w) / 2,
+        area.y + (area.height - h) / 2,
+        w,
+        h,
+    );
+    frame.render_widget(ClearWidget, rect);
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::bordered().title(Line::from("legend").centered())),
+        rect,
+    );
+}
+
+// the ui is part of the binary only, so its tests can't live under tests/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::buffer::Buffer;
+    use ratatui::style::Color;
+
+    /// An app that reads nothing from disk, unlike [`App::new`]
+    fn app(width: u16, height: u16) -> App {
+        let args = MinesweeperArgs::builder()
+            .width(width)
+            .height(height)
+            .seed(1)
+            .build()
+            .unwrap();
+        let mut game = Minesweeper::new(args);
+        game.input_state.cursor = (width / 2, height / 2);
+        App {
+            game,
+            recenter: true,
+            ..App::default()
+        }
+    }
+
+    fn draw(app: &mut App, width: u16, height: u16) -> Buffer {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal.draw(|frame| app.render(frame)).unwrap();
+        terminal.backend().buffer().clone()
+    }
+
+    #[test]
+    fn big_board_in_a_tiny_area() {
+        let mut app = app(32, 16);
+        draw(&mut app, 3, 3);
+        app.game.open(16, 8);
+        app.game.input_state.cursor = (31, 15);
+        draw(&mut app, 3, 3);
+    }
+
+    #[test]
+    fn big_board_scrolls_in_a_small_area() {
+        let mut app = app(32, 16);
+        app.game.open(16, 8);
+        app.game.input_state.cursor = (31, 15);
+        let buffer = draw(&mut app, 12, 12);
+        assert_eq!(buffer.area, Rect::new(0, 0, 12, 12));
+        // the far corner is scrolled into view, next to the border
+        let cell = app.game.get_tile(31, 15).unwrap();
+        let (char, ..) = Theme::from(app.game.args.theme).tile_style(cell);
+        assert_eq!(buffer[(10, 10)].symbol(), char.to_string());
+    }
+
+    fn rows(buffer: &Buffer) -> Vec<String> {
+        let width = buffer.area.width.max(1) as usize;
+        buffer
+            .content
+            .chunks(width)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn tiny_areas_ask_for_a_bigger_terminal() {
+        for (width, height) in [(0, 0), (1, 0), (0, 1), (1, 1), (2, 5), (5, 2), (3, 3)] {
+            draw(&mut app(8, 8), width, height);
+        }
+        for (width, height) in [(9, 9), (40, 3), (9, 40)] {
+            let buffer = draw(&mut app(8, 8), width, height);
+            assert!(rows(&buffer).iter().any(|row| row.contains("enlarge")));
+        }
+        let buffer = draw(&mut app(8, 8), 10, 10);
+        assert!(!rows(&buffer).iter().any(|row| row.contains("enlarge")));
+    }
+
+    /// An 8x8 board with 6 mines and the cursor in the middle
+    fn small_app() -> App {
+        let args = MinesweeperArgs::builder()
+            .width(8)
+            .height(8)
+            .mines(6)
+            .seed(5)
+            .build()
+            .unwrap();
+        let mut game = Minesweeper::new(args);
+        game.input_state.cursor = (4, 4);
+        App {
+            game,
+            recenter: true,
+            ..App::default()
+        }
+    }
+
+    fn assert_style(buffer: &Buffer, (x, y): (u16, u16), fg: Color, bg: Color) {
+        let cell = &buffer[(x, y)];
+        assert_eq!((cell.fg, cell.bg), (fg, bg), "at {x},{y}");
+    }
+
+    #[test]
+    fn untouched_board_snapshot() {
+        let mut app = small_app();
+        let theme = Theme::from(app.game.args.theme);
+        let buffer = draw(&mut app, 10, 10);
+        #[rustfmt::skip]
+        assert_eq!(rows(&buffer), [
+            "┌mnswpr!!┐",
+            "│########│",
+            "│########│",
+            "│########│",
+            "│########│",
+            "│########│",
+            "│########│",
+            "│########│",
+            "│########│",
+            "└─6 (9%)─┘",
+        ]);
+        assert_style(&buffer, (1, 1), theme.hidden.0, theme.hidden.1);
+        assert_style(&buffer, (5, 5), theme.hidden.0, theme.cursor);
+    }
+
+    #[test]
+    fn ongoing_board_snapshot() {
+        let mut app = small_app();
+        let theme = Theme::from(app.game.args.theme);
+        app.game.open(4, 4);
+        app.game.flag(0, 0);
+        let buffer = draw(&mut app, 10, 10);
+        #[rustfmt::skip]
+        assert_eq!(rows(&buffer), [
+            "┌mnswpr!!┐",
+            "│!##1    │",
+            "│###1    │",
+            "│#211    │",
+            "│#1      │",
+            "│#1      │",
+            "│#1      │",
+            "│#2321   │",
+            "│####1   │",
+            "└1 (5,5)─┘",
+        ]);
+        assert_style(&buffer, (1, 1), theme.flagged.0, theme.flagged.1);
+        assert_style(&buffer, (2, 1), theme.hidden.0, theme.hidden.1);
+        assert_style(&buffer, (4, 1), theme.numbers[0], theme.revealed);
+        assert_style(&buffer, (2, 3), theme.numbers[1], theme.revealed);
+        assert_style(&buffer, (8, 8), Color::Reset, theme.revealed);
+        assert_style(&buffer, (5, 5), Color::Reset, theme.cursor);
+    }
+
+    #[test]
+    fn won_board_snapshot() {
+        let mut app = small_app();
+        let theme = Theme::from(app.game.args.theme);
+        app.game.open(4, 4);
+        for i in 0..64 {
+            if app.game.game_state.cells[i].content != Mine {
+                app.game.open(i as u16 % 8, i as u16 / 8);
+            }
+        }
+        assert!(matches!(app.game.game_state.win_state, WinState::Won));
+        let buffer = draw(&mut app, 10, 10);
+        #[rustfmt::skip]
+        assert_eq!(rows(&buffer), [
+            "┌(R) (Q)─┐",
+            "│1211    │",
+            "│#2#1    │",
+            "│1211    │",
+            "│11      │",
+            "│#1      │",
+            "│11      │",
+            "│12321   │",
+            "│1###1   │",
+            "└(N) (P)─┘",
+        ]);
+        assert_style(&buffer, (1, 2), theme.hidden.0, theme.hidden.1);
+        assert_style(&buffer, (2, 7), theme.numbers[1], theme.revealed);
+        assert_style(&buffer, (3, 7), theme.numbers[2], theme.revealed);
+    }
+
+    #[test]
+    fn more_flags_than_mines_count_below_zero() {
+        let mut app = small_app();
+        app.show_remaining = true;
+        app.game.open(4, 4);
+        let hidden: Vec<usize> = (0..64)
+            .filter(|&i| matches!(app.game.game_state.cells[i].visibility, Hidden(_)))
+            .take(10)
+            .collect();
+        for i in hidden {
+            app.game.flag(i as u16 % 8, i as u16 / 8);
+        }
+        assert_eq!(app.game.game_state.flagged_cells, 10);
+        let footer = rows(&draw(&mut app, 10, 10)).pop().unwrap();
+        assert!(footer.starts_with("└-4"), "{footer}");
+
+        app.show_remaining = false;
+        let footer = rows(&draw(&mut app, 10, 10)).pop().unwrap();
+        assert!(footer.starts_with("└10"), "{footer}");
+    }
+
+    #[test]
+    fn default_undo_and_redo_keys_do_nothing_else() {
+        let keymap = Keymap::default();
+        let bindings =
+            (keymap.undo.iter().map(|b| (b, Undo))).chain(keymap.redo.iter().map(|b| (b, Redo)));
+        for (binding, expected) in bindings {
+            let mut app = small_app();
+            app.running = true;
+            app.on_key_event(KeyEvent::new(binding.code, binding.modifiers));
+            let action = format!("{:?}", app.game.input_state.action);
+            assert_eq!(action, format!("{:?}", Some(Debug(expected))), "{binding}");
+            assert_eq!(app.game.input_state.cursor, (4, 4));
+            assert_eq!((app.game.args.width, app.game.args.height), (8, 8));
+            assert!(app.running);
+        }
+    }
+
+    #[test]
+    fn watching_ignores_keys_that_change_the_game() {
+        use crossterm::event::KeyCode::{Char, Left, Right};
+        let (_tx, rx) = std::sync::mpsc::channel();
+        let mut app = small_app();
+        app.watching = Some(rx);
+        app.running = true;
+        let args = format!("{:?}", app.game.args);
+        for (modifiers, code) in [
+            (KeyModifiers::NONE, Char('Y')),
+            (KeyModifiers::NONE, Char('L')),
+            (KeyModifiers::NONE, Char('D')),
+            (KeyModifiers::NONE, Char('r')),
+            (KeyModifiers::NONE, Char('n')),
+            (KeyModifiers::NONE, Char('k')),
+            (KeyModifiers::NONE, Char('x')),
+            (KeyModifiers::NONE, Char('m')),
+            (KeyModifiers::SHIFT, Right),
+            (KeyModifiers::CONTROL, Char('z')),
+            (KeyModifiers::CONTROL, Left),
+        ] {
+            app.on_key_event(KeyEvent::new(code, modifiers));
+            assert!(app.game.input_state.action.is_none(), "{code:?}");
+            assert!(!app.recording);
+        }
+        assert_eq!(format!("{:?}", app.game.args), args);
+
+        app.on_key_event(KeyEvent::new(Char('v'), KeyModifiers::NONE));
+        assert!(app.free_scroll);
+        app.on_key_event(KeyEvent::new(Right, KeyModifiers::NONE));
+        assert_eq!(app.game.input_state.cursor, (5, 4));
+        app.on_key_event(KeyEvent::new(Char('q'), KeyModifiers::NONE));
+        assert!(!app.running);
+    }
+
+    /// Everything a key press could change, short of the game itself
+    fn key_effects(app: &App) -> String {
+        format!(
+            "{:?} {:?} {:?} {:?}",
+            app.game.input_state,
+            app.game.args,
+            (
+                app.running,
+                app.recording,
+                app.free_scroll,
+                app.show_remaining
+            ),
+            (
+                app.show_log,
+                app.show_recent,
+                app.show_cross,
+                app.show_inspect
+            ),
+        )
+    }
+
+    #[test]
+    fn builtin_keys_list_every_key_the_game_handles() {
+        use minesweeper::config::BUILTIN_KEYS;
+
+        let mut keys: Vec<KeyCode> = (' '..='~').map(KeyCode::Char).collect();
+        keys.extend([KeyCode::Enter, KeyCode::Tab, KeyCode::Home, KeyCode::End]);
+        keys.extend([KeyCode::Delete, KeyCode::F(1)]);
+        keys.extend(BUILTIN_KEYS);
+        for code in keys {
+            for modifiers in [KeyModifiers::NONE, KeyModifiers::CONTROL, KeyModifiers::ALT] {
+                let mut app = small_app();
+                app.running = true;
+                // the keymap is tested on its own
+                app.keymap = Keymap {
+                    undo: vec![],
+                    redo: vec![],
+                };
+                let before = key_effects(&app);
+                app.on_key_event(KeyEvent::new(code, modifiers));
+                let quit =
+                    modifiers == KeyModifiers::CONTROL && matches!(code, KeyCode::Char('c' | 'C'));
+                let builtin = modifiers == KeyModifiers::NONE && BUILTIN_KEYS.contains(&code);
+                if !quit && !builtin {
+                    assert_eq!(key_effects(&app), before, "{modifiers:?} {code:?}");
+                    assert!(app.status_message.is_none() && !app.show_legend);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn zen_and_practice_games_are_not_counted() {
+        for (zen, practice) in [(true, false), (false, true)] {
+            let mut app = small_app();
+            app.game.args.zen = zen;
+            app.game.args.practice = practice;
+            for win_state in [WinState::Lost, WinState::Won] {
+                app.game.game_state.win_state = win_state;
+                app.record_stats();
+            }
+            assert!(!app.game.stats_recorded);
+        }
+    }
+}
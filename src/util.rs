@@ -1,6 +1,5 @@
 use crate::action::Cursor;
 use rand::RngCore;
-use std::collections::BTreeSet;
 
 pub const DIRS_8: [(i8, i8); 8] = [
     (1, 0),
@@ -12,18 +11,6 @@ pub const DIRS_8: [(i8, i8); 8] = [
     (0, -1),
     (1, -1),
 ];
-pub const DIRS_9: [(i8, i8); 9] = [
-    (0, 0),
-    (1, 0),
-    (1, 1),
-    (0, 1),
-    (-1, 1),
-    (-1, 0),
-    (-1, -1),
-    (0, -1),
-    (1, -1),
-];
-
 #[derive(Copy, Clone, Debug)]
 pub enum Sign {
     Negative = -1,
@@ -66,37 +53,68 @@ pub fn valid_neighbors(
         })
 }
 
-pub fn fill_random<T: PartialEq + Copy>(
+/// The (2 * radius + 1) square around the cursor, moved inside the board at its edges so it keeps
+/// its full size wherever the board is large enough. With a radius of 1 or more it always contains the
+/// cursor's neighbors, so a dense board still opens a whole square around a first click at the edge.
+pub fn safe_square((x, y): Cursor, radius: u16, w: u16, h: u16) -> impl Iterator<Item = Cursor> {
+    let side = 2 * radius + 1;
+    let (left, top) = (
+        x.saturating_sub(radius).min(w.saturating_sub(side)),
+        y.saturating_sub(radius).min(h.saturating_sub(side)),
+    );
+    let xs = left..=(left + side - 1).min(w - 1);
+    let ys = top..=(top + side - 1).min(h - 1);
+    ys.flat_map(move |j| xs.clone().map(move |i| (i, j)))
+}
+
+/// Sets `fills` random indices to `value`, never touching the whitelisted ones.
+/// Runs in O(size) so it stays fast on very large boards.
+///
+/// The pick only depends on the rng, `size`, `fills` and the set of whitelisted indices, not their order:
+/// the other indices are lined up in ascending order and the k-th fill swaps in one of the candidates
+/// from k on, chosen by a single `next_u32() % remaining`. Exactly `fills` numbers are drawn, or fewer
+/// when the whitelist leaves fewer candidates. With a seeded `StdRng` the same seed gives the same
+/// layout for as long as rand keeps `StdRng`'s algorithm, which can change in a major release.
+///
+/// ```
+/// use minesweeper::util::fill_random;
+/// use rand::SeedableRng;
+/// use rand::rngs::StdRng;
+///
+/// // a 5x5 board with the 3x3 square in the middle kept free
+/// let whitelisted = [6, 7, 8, 11, 12, 13, 16, 17, 18];
+/// let mines = fill_random(&mut StdRng::seed_from_u64(7), whitelisted.into_iter(), 25, 5, false, true);
+/// let picked: Vec<usize> = (0..25).filter(|&i| mines[i]).collect();
+/// assert_eq!(picked, [0, 4, 5, 9, 23]);
+///
+/// // the same whitelist in another order picks the same cells
+/// let again = fill_random(&mut StdRng::seed_from_u64(7), whitelisted.into_iter().rev(), 25, 5, false, true);
+/// assert_eq!(mines, again);
+/// ```
+pub fn fill_random<T: Copy>(
+    rng: &mut impl RngCore,
     whitelisted: impl Iterator<Item = usize>,
     size: usize,
     fills: usize,
     init_value: T,
     value: T,
 ) -> Vec<T> {
-    let mut whitelisted: BTreeSet<usize> = BTreeSet::from_iter(whitelisted);
-    let (fills, init_value, value, flip) = if fills > size / 2 {
-        (size - fills - whitelisted.len(), value, init_value, true)
-    } else {
-        (fills, init_value, value, false)
-    };
-
-    let mut ret = vec![init_value; size];
-
-    if flip {
-        for wl in &whitelisted {
-            ret[*wl] = value
-        }
+    let mut excluded = vec![false; size];
+    for wl in whitelisted {
+        excluded[wl] = true;
     }
+    let mut candidates: Vec<usize> = (0..size).filter(|&i| !excluded[i]).collect();
+    let fills = fills.min(candidates.len());
 
-    for _ in 0..fills {
-        let mut r = rand::rng().next_u32() as usize % (ret.len() - whitelisted.len());
-
-        for wl in whitelisted.iter() {
-            r = if *wl <= r { r + 1 } else { break };
-        }
-        ret[r] = value;
-        whitelisted.insert(r);
+    // partial fisher-yates, the first `fills` candidates end up being a uniform pick
+    for k in 0..fills {
+        let r = k + rng.next_u32() as usize % (candidates.len() - k);
+        candidates.swap(k, r);
     }
 
+    let mut ret = vec![init_value; size];
+    for &i in &candidates[..fills] {
+        ret[i] = value;
+    }
     ret
 }
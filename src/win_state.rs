@@ -1,13 +1,12 @@
-#[derive(Copy, Clone, Debug)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize)]
 pub enum WinState {
+    #[default]
     Untouched,
     Ongoing,
     Lost,
     Won,
-}
-
-impl Default for WinState {
-    fn default() -> Self {
-        Self::Untouched
-    }
+    /// whole board shown from the start, nothing can be played
+    Revealed,
 }
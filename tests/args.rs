@@ -0,0 +1,176 @@
+use minesweeper::args::{ArgsError, MinesweeperArgs};
+use minesweeper::minesweeper::Minesweeper;
+use minesweeper::win_state::WinState;
+
+#[test]
+fn five_by_five_at_relaxed_minimum() {
+    let mut args = MinesweeperArgs::builder().seed(3).build().unwrap();
+    args.min_size = 5;
+    args.width = 5;
+    args.height = 5;
+    args.mines = 16;
+    assert_eq!(args.validate(), Ok(()));
+
+    let mut args = args.clamped();
+    assert_eq!((args.width, args.height, args.mines), (5, 5, 16));
+    // the 3x3 safe square still fits next to the mines
+    args.mines = 17;
+    assert_eq!(
+        args.validate(),
+        Err(ArgsError::TooManyMines {
+            mines: 17,
+            max: 16,
+            safe_radius: 1
+        })
+    );
+
+    let mut game = Minesweeper::new(args.clamped());
+    assert_eq!(game.args.mines, 16);
+    game.open(0, 0);
+    assert!(matches!(game.game_state.win_state, WinState::Won));
+}
+
+#[test]
+fn default_minimum_still_clamps() {
+    let args = MinesweeperArgs::builder()
+        .width(5)
+        .height(5)
+        .build()
+        .unwrap();
+    assert_eq!((args.width, args.height), (8, 8));
+}
+
+#[test]
+fn single_cell_board_has_no_mine() {
+    let mut args = MinesweeperArgs::builder().build().unwrap();
+    args.min_size = 1;
+    args.width = 1;
+    args.height = 1;
+    args.mines = 1;
+    assert_eq!(args.validate(), Err(ArgsError::SafeRadiusCoversBoard(1)));
+    args.safe_radius = 0;
+    assert_eq!(
+        args.validate(),
+        Err(ArgsError::TooManyMines {
+            mines: 1,
+            max: 0,
+            safe_radius: 0
+        })
+    );
+
+    let mut game = Minesweeper::new(args);
+    assert_eq!(game.args.mines, 0);
+    assert!(game.open(0, 0).is_some());
+    assert!(matches!(game.game_state.win_state, WinState::Won));
+}
+
+#[test]
+fn explicit_mines_are_noted() {
+    use clap::CommandFactory;
+    use minesweeper::args::Cli;
+
+    let parse = |args: &[&str]| {
+        let matches = Cli::command().get_matches_from(args);
+        Cli::from_matches(&matches).unwrap()
+    };
+    let fit = parse(&["minesweeper", "--fit"]);
+    assert!(!fit.mines_given);
+    assert_eq!(fit.game.mines_percent, None);
+
+    let fit = parse(&["minesweeper", "--fit", "-m", "30"]);
+    assert!(fit.mines_given);
+    assert_eq!(fit.game.mines, 30);
+
+    // typing out the default count still keeps it
+    let fit = parse(&["minesweeper", "--fit", "-m", "100"]);
+    assert!(fit.mines_given);
+    let fit = parse(&["minesweeper", "--fit", "--mines-percent", "10"]);
+    assert!(!fit.mines_given);
+}
+
+#[test]
+fn mines_percent_at_the_bounds() {
+    let mut args = MinesweeperArgs::builder()
+        .width(8)
+        .height(8)
+        .build()
+        .unwrap();
+    args.mines_percent = Some(0.0);
+    assert_eq!(args.validate(), Err(ArgsError::NoMines));
+    // clamping keeps one mine
+    assert_eq!(args.clamped().mines, 1);
+
+    args.mines_percent = Some(100.0);
+    assert_eq!(
+        args.validate(),
+        Err(ArgsError::TooManyMines {
+            mines: 64,
+            max: 55,
+            safe_radius: 1
+        })
+    );
+    assert_eq!(args.clamped().mines, 55);
+
+    args.mines_percent = Some(100.5);
+    assert_eq!(args.validate(), Err(ArgsError::InvalidDensity(100.5)));
+
+    let args = MinesweeperArgs::builder()
+        .width(8)
+        .height(8)
+        .mines_percent(100.0)
+        .build()
+        .unwrap();
+    assert_eq!((args.mines, args.mines_percent), (55, None));
+}
+
+#[test]
+fn puzzle_boards_have_a_size_limit() {
+    let puzzle = |width: u16| {
+        let mut args = MinesweeperArgs::builder()
+            .width(width)
+            .height(200)
+            .mines_percent(1.0)
+            .seed(1)
+            .build()
+            .unwrap();
+        args.puzzle = true;
+        args
+    };
+    assert!(puzzle(200).validate().is_ok());
+    let large = puzzle(201);
+    assert_eq!(
+        large.validate(),
+        Err(ArgsError::PuzzleTooLarge {
+            width: 201,
+            height: 200
+        })
+    );
+    // without --strict it is an ordinary board
+    assert!(Minesweeper::new(large).puzzle_fallback);
+}
+
+#[test]
+fn odd_puzzle_mines_and_no_lives() {
+    let mut args = MinesweeperArgs::builder()
+        .width(8)
+        .height(8)
+        .mines(9)
+        .build()
+        .unwrap();
+    args.puzzle = true;
+    assert_eq!(args.validate(), Err(ArgsError::OddPuzzleMines(9)));
+    assert_eq!(args.clamped().mines, 8);
+    args.mines = 8;
+    assert!(args.validate().is_ok());
+    // past the limit it is still too many, whatever the parity
+    args.mines = 57;
+    assert!(matches!(
+        args.validate(),
+        Err(ArgsError::TooManyMines { mines: 57, .. })
+    ));
+
+    args.mines = 8;
+    args.lives = 0;
+    assert_eq!(args.validate(), Err(ArgsError::NoLives));
+    assert_eq!(args.clamped().lives, 1);
+}
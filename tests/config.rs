@@ -0,0 +1,129 @@
+use minesweeper::config::{Config, ConfigError, ConfigErrorKind};
+
+fn error(text: &str) -> ConfigError {
+    Config::parse(text).unwrap_err()
+}
+
+fn invalid(key: &str, value: &str) -> ConfigErrorKind {
+    ConfigErrorKind::InvalidValue {
+        key: key.to_string(),
+        value: value.to_string(),
+    }
+}
+
+#[test]
+fn valid_config() {
+    let config = Config::parse(
+        "# comment\n\ntheme = dark\nscroll_step = 3 # trailing\ntime_thresholds = 60, 180\nundo = u\n",
+    )
+    .unwrap();
+    assert_eq!(config.scroll_step, Some(3));
+    assert_eq!(config.time_thresholds, Some((60, 180)));
+    assert_eq!(config.keymap.undo.len(), 1);
+    assert_eq!(config.keymap.redo.len(), 2);
+}
+
+#[test]
+fn malformed_lines() {
+    let e = error("theme dark");
+    assert_eq!((e.line, e.kind), (1, ConfigErrorKind::Syntax));
+
+    let e = error("theme = dark\ncolour = red");
+    assert_eq!(
+        (e.line, e.kind),
+        (2, ConfigErrorKind::UnknownKey("colour".to_string()))
+    );
+    assert_eq!(
+        error("colour = red").to_string(),
+        "line 1: unknown key `colour`"
+    );
+}
+
+#[test]
+fn invalid_values() {
+    assert_eq!(error("theme = neon").kind, invalid("theme", "neon"));
+    assert_eq!(error("cursor = arrow").kind, invalid("cursor", "arrow"));
+    assert_eq!(error("scroll_step = 0").kind, invalid("scroll_step", "0"));
+    assert_eq!(error("scroll_step = -1").kind, invalid("scroll_step", "-1"));
+    assert_eq!(
+        error("time_thresholds = 60").kind,
+        invalid("time_thresholds", "60")
+    );
+    assert_eq!(
+        error("time_thresholds = 180, 60").kind,
+        invalid("time_thresholds", "180, 60")
+    );
+    assert_eq!(error("undo = ctrl+zz").kind, invalid("undo", "ctrl+zz"));
+    assert_eq!(error("redo = hyper+y").kind, invalid("redo", "hyper+y"));
+    assert_eq!(
+        error("theme = neon").to_string(),
+        "line 1: invalid theme `neon`"
+    );
+}
+
+#[test]
+fn duplicates() {
+    let e = error("theme = dark\n\ntheme = light");
+    assert_eq!(
+        (e.line, e.kind),
+        (3, ConfigErrorKind::DuplicateKey("theme".to_string()))
+    );
+
+    let e = error("undo = ctrl+q\nscroll_step = 2\nredo = alt+x, ctrl+q\n");
+    assert_eq!(
+        (e.line, e.kind),
+        (3, ConfigErrorKind::DuplicateBinding("ctrl+q".to_string()))
+    );
+    // the default redo keys clash with undo set to one of them
+    let e = error("undo = ctrl+y");
+    assert_eq!(
+        (e.line, e.kind),
+        (1, ConfigErrorKind::DuplicateBinding("ctrl+y".to_string()))
+    );
+}
+
+#[test]
+fn default_bindings_do_one_thing() {
+    use crossterm::event::{KeyEvent, KeyModifiers};
+    use minesweeper::config::Keymap;
+
+    let Keymap { undo, redo } = Keymap::default();
+    for binding in undo.iter().chain(&redo) {
+        for modifiers in [binding.modifiers, binding.modifiers | KeyModifiers::SHIFT] {
+            let key = KeyEvent::new(binding.code, modifiers);
+            let undoes = undo.iter().any(|b| b.matches(&key));
+            let redoes = redo.iter().any(|b| b.matches(&key));
+            assert!(!(undoes && redoes), "{binding} does both");
+            let builtin = (modifiers - KeyModifiers::SHIFT).is_empty()
+                && minesweeper::config::BUILTIN_KEYS.contains(&binding.code);
+            assert!(!builtin, "{binding} is also a built in key");
+        }
+        assert!(!binding.is_reserved(), "{binding} is reserved");
+    }
+}
+
+#[test]
+fn reserved_bindings() {
+    for (text, binding) in [
+        ("undo = ctrl+c", "ctrl+c"),
+        ("undo = c", "c"),
+        ("redo = k", "k"),
+        ("undo = left", "left"),
+        ("undo = u, shift+left", "shift+left"),
+        ("redo = ctrl+y, esc", "esc"),
+    ] {
+        assert_eq!(
+            error(text).kind,
+            ConfigErrorKind::ReservedBinding(binding.to_string()),
+            "{text}"
+        );
+    }
+    for text in [
+        "undo = u",
+        "undo = ctrl+k",
+        "redo = alt+left",
+        "undo = shift+u",
+    ] {
+        assert!(Config::parse(text).is_ok(), "{text}");
+    }
+}
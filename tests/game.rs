@@ -0,0 +1,720 @@
+use minesweeper::args::{Difficulty, MinesweeperArgs};
+use minesweeper::cell_content::CellContent::{Empty, Mine};
+use minesweeper::flag::Flag::Clear;
+use minesweeper::minesweeper::{GameState, Minesweeper};
+use minesweeper::tile_visibility::TileVisibility::Hidden;
+use minesweeper::util::{DIRS_8, valid_neighbors, xy_i};
+use minesweeper::win_state::WinState;
+
+fn xy(i: usize, w: u16) -> (u16, u16) {
+    ((i % w as usize) as u16, (i / w as usize) as u16)
+}
+
+/// Opens every empty cell that is still hidden, one by one
+fn open_all_empty(game: &mut Minesweeper) {
+    let w = game.args.width;
+    for i in 0..game.game_state.cells.len() {
+        let cell = game.game_state.cells[i];
+        if matches!(cell.visibility, Hidden(_)) && cell.content != Mine {
+            let (x, y) = xy(i, w);
+            game.open(x, y);
+        }
+    }
+}
+
+#[test]
+fn perfect_survives_clicks_after_the_win() {
+    let args = MinesweeperArgs::builder()
+        .difficulty(Difficulty::Beginner)
+        .seed(11)
+        .build()
+        .unwrap();
+    let mut game = Minesweeper::new(args);
+    game.open(4, 4);
+    open_all_empty(&mut game);
+    assert!(matches!(game.game_state.win_state, WinState::Won));
+    assert!(game.game_state.perfect);
+
+    assert!(game.open(4, 4).is_none());
+    assert!(game.chord(4, 4).is_none());
+    assert!(game.game_state.perfect);
+}
+
+#[test]
+fn perfect_lost_on_a_click_that_opens_nothing() {
+    let args = MinesweeperArgs::builder()
+        .difficulty(Difficulty::Beginner)
+        .seed(11)
+        .build()
+        .unwrap();
+    let mut game = Minesweeper::new(args);
+    game.open(4, 4);
+    assert!(game.open(4, 4).is_none());
+    assert!(!game.game_state.perfect);
+}
+
+#[test]
+fn wrong_flag_reported_when_a_flood_opens_it() {
+    use minesweeper::action::{Action::Debug, DebugAction::Undo};
+    use minesweeper::tile_visibility::TileVisibility::Show;
+
+    let mut args = MinesweeperArgs::builder()
+        .difficulty(Difficulty::Beginner)
+        .seed(11)
+        .build()
+        .unwrap();
+    args.practice = true;
+    let mut game = Minesweeper::new(args);
+    let flood = game.open(4, 4).unwrap().indices();
+    let start = 4 * 9 + 4;
+    let target = *flood.iter().find(|&&i| i != start).unwrap();
+
+    // take the open back, the mines stay where they are
+    game.input_state.action = Some(Debug(Undo));
+    game.update();
+    let (x, y) = xy(target, 9);
+    let placed = game.flag(x, y).unwrap();
+    assert!(placed.opened_wrong_flags().is_empty());
+
+    let reopened = game.open(4, 4).unwrap();
+    assert_eq!(reopened.opened_wrong_flags(), [target]);
+    assert_eq!(game.game_state.cells[target].visibility, Show);
+}
+
+#[test]
+fn puzzle_fallback_is_reported() {
+    let puzzle = |mines: u32| {
+        let mut args = MinesweeperArgs::builder()
+            .difficulty(Difficulty::Beginner)
+            .mines(mines)
+            .seed(1)
+            .build()
+            .unwrap();
+        args.puzzle = true;
+        Minesweeper::new(args)
+    };
+    let easy = puzzle(10);
+    assert!(matches!(easy.game_state.win_state, WinState::Ongoing));
+    assert!(!easy.puzzle_fallback);
+
+    // no symmetric layout at this density can be solved without guessing
+    let dense = puzzle(60);
+    assert!(matches!(dense.game_state.win_state, WinState::Ongoing));
+    assert!(dense.puzzle_fallback);
+}
+
+fn mine_cells(game: &Minesweeper) -> Vec<usize> {
+    let cells = &game.game_state.cells;
+    (0..cells.len())
+        .filter(|&i| cells[i].content == Mine)
+        .collect()
+}
+
+#[test]
+fn classic_win_needs_only_the_empty_cells_open() {
+    let args = MinesweeperArgs::builder()
+        .difficulty(Difficulty::Beginner)
+        .seed(11)
+        .build()
+        .unwrap();
+    let mut game = Minesweeper::new(args);
+    game.open(4, 4);
+    for i in mine_cells(&game) {
+        let (x, y) = xy(i, 9);
+        game.flag(x, y);
+    }
+    assert!(matches!(game.game_state.win_state, WinState::Ongoing));
+
+    // the flags come off again, an unflagged board wins all the same
+    for i in mine_cells(&game) {
+        let (x, y) = xy(i, 9);
+        game.flag(x, y);
+        game.flag(x, y);
+    }
+    open_all_empty(&mut game);
+    assert_eq!(game.game_state.flagged_cells, 0);
+    assert!(matches!(game.game_state.win_state, WinState::Won));
+}
+
+#[test]
+fn flag_win_needs_every_mine_flagged() {
+    let mut args = MinesweeperArgs::builder()
+        .difficulty(Difficulty::Beginner)
+        .seed(11)
+        .build()
+        .unwrap();
+    args.win_on_flags = true;
+    let mut game = Minesweeper::new(args);
+    game.open(4, 4);
+    open_all_empty(&mut game);
+    assert_eq!(game.game_state.closed_empty_cells, 0);
+    assert!(matches!(game.game_state.win_state, WinState::Ongoing));
+
+    // a maybe flag is no flag, it goes on first since the last real flag wins
+    let mines = mine_cells(&game);
+    let (first, rest) = mines.split_first().unwrap();
+    let (x, y) = xy(*first, 9);
+    game.flag(x, y);
+    game.flag(x, y);
+    for &i in rest {
+        let (x, y) = xy(i, 9);
+        game.flag(x, y);
+    }
+    assert_eq!(game.game_state.unflagged_mines, 1);
+    assert!(matches!(game.game_state.win_state, WinState::Ongoing));
+
+    game.flag(x, y);
+    game.flag(x, y);
+    assert!(matches!(game.game_state.win_state, WinState::Won));
+}
+
+#[test]
+fn flag_win_needs_no_empty_cell_flagged() {
+    let mut args = MinesweeperArgs::builder()
+        .difficulty(Difficulty::Beginner)
+        .seed(11)
+        .build()
+        .unwrap();
+    args.win_on_flags = true;
+    let mut game = Minesweeper::new(args);
+    game.open(4, 4);
+    for i in mine_cells(&game) {
+        let (x, y) = xy(i, 9);
+        game.flag(x, y);
+    }
+    // a number no flood reaches, so the flag stays until it's taken off
+    let cells = &game.game_state.cells;
+    let empty = (0..cells.len())
+        .find(|&i| {
+            let next_to_zero = valid_neighbors(&DIRS_8, xy(i, 9), 9, 9)
+                .any(|n| cells[xy_i(n, 9, 9).unwrap()].content == Empty(0));
+            matches!(cells[i].visibility, Hidden(_))
+                && matches!(cells[i].content, Empty(1..))
+                && !next_to_zero
+        })
+        .unwrap();
+    let (x, y) = xy(empty, 9);
+    game.flag(x, y);
+    open_all_empty(&mut game);
+    assert_eq!(game.game_state.unflagged_mines, 0);
+    assert_eq!(game.game_state.closed_empty_cells, 1);
+    assert!(matches!(game.game_state.win_state, WinState::Ongoing));
+
+    game.flag(x, y);
+    game.flag(x, y);
+    game.open(x, y);
+    assert!(matches!(game.game_state.win_state, WinState::Won));
+}
+
+#[test]
+fn shrinking_keeps_the_cursor_on_the_board() {
+    use minesweeper::action::{
+        Action::Restart,
+        RestartAction::{ResizeH, ResizeV},
+    };
+    use minesweeper::util::Sign::Negative;
+
+    let args = MinesweeperArgs::builder()
+        .width(32)
+        .height(16)
+        .seed(3)
+        .build()
+        .unwrap();
+    let mut game = Minesweeper::new(args);
+    for resize in [ResizeH(Negative), ResizeV(Negative)] {
+        for _ in 0..40 {
+            game.input_state.cursor = (game.args.width - 1, game.args.height - 1);
+            game.input_state.action = Some(Restart(Some(resize)));
+            game.update();
+            let (x, y) = game.input_state.cursor;
+            assert!(x < game.args.width && y < game.args.height);
+            assert!(game.get_tile(x, y).is_some());
+        }
+    }
+    assert_eq!(
+        (game.args.width, game.args.height),
+        (game.args.min_size, game.args.min_size)
+    );
+}
+
+#[test]
+fn million_cell_board() {
+    use minesweeper::util::i_xy;
+
+    let args = MinesweeperArgs::builder()
+        .width(1000)
+        .height(1000)
+        .mines_percent(15.0)
+        .seed(2)
+        .build()
+        .unwrap();
+    let mut game = Minesweeper::new(args);
+    game.open(500, 500);
+    let state = &game.game_state;
+    assert_eq!(state.cells.len(), 1_000_000);
+    assert_eq!(mine_cells(&game).len(), 150_000);
+    let open = state
+        .cells
+        .iter()
+        .filter(|cell| !matches!(cell.visibility, Hidden(_)))
+        .count();
+    assert_eq!(open as u32 + state.closed_empty_cells + 150_000, 1_000_000);
+    assert_eq!(xy_i((999, 999), 1000, 1000), Some(999_999));
+    assert_eq!(i_xy(999_999, 1000, 1000), Some((999, 999)));
+}
+
+#[test]
+fn reveal_leaves_the_game_to_the_player() {
+    // 99% of the 44 empty cells rounds up to all of them
+    for seed in 0..20 {
+        let mut args = MinesweeperArgs::builder()
+            .width(8)
+            .height(8)
+            .mines(20)
+            .seed(seed)
+            .build()
+            .unwrap();
+        args.reveal = 99;
+        let game = Minesweeper::new(args.clamped());
+        let state = &game.game_state;
+        assert!(matches!(state.win_state, WinState::Ongoing), "seed {seed}");
+        assert!(state.closed_empty_cells > 0);
+        assert!(game.finished.is_none() && game.history.entries.is_empty());
+    }
+}
+
+/// How far the nearest mine is from the center of a 9x9 board opened there first, in cells
+fn nearest_mine(safe_radius: u8, seed: u64) -> u16 {
+    let mut args = MinesweeperArgs::builder()
+        .width(9)
+        .height(9)
+        .mines(40)
+        .seed(seed)
+        .build()
+        .unwrap();
+    args.safe_radius = safe_radius;
+    let mut game = Minesweeper::new(args);
+    game.open(4, 4);
+    mine_cells(&game)
+        .into_iter()
+        .map(|i| {
+            let (x, y) = xy(i, 9);
+            x.abs_diff(4).max(y.abs_diff(4))
+        })
+        .min()
+        .unwrap()
+}
+
+#[test]
+fn safe_radius_zero_only_spares_the_clicked_cell() {
+    let nearest: Vec<u16> = (0..20).map(|seed| nearest_mine(0, seed)).collect();
+    assert!(nearest.iter().all(|&d| d >= 1));
+    assert!(nearest.contains(&1));
+}
+
+#[test]
+fn safe_radius_spares_the_square_around_the_click() {
+    for seed in 0..20 {
+        assert!(nearest_mine(1, seed) >= 2);
+        assert!(nearest_mine(2, seed) >= 3);
+    }
+}
+
+#[test]
+fn cloned_game_advances_independently() {
+    let args = MinesweeperArgs::builder()
+        .difficulty(Difficulty::Beginner)
+        .seed(11)
+        .build()
+        .unwrap();
+    let mut game = Minesweeper::new(args);
+    game.open(4, 4);
+    let before = game.to_string();
+    let moves = game.game_state.moves;
+
+    let mut clone = game.clone();
+    open_all_empty(&mut clone);
+    assert!(matches!(clone.game_state.win_state, WinState::Won));
+    assert!(clone.game_state.moves > moves);
+
+    assert_eq!(game.to_string(), before);
+    assert_eq!(game.game_state.moves, moves);
+    assert_eq!(game.history.applied().len(), moves as usize);
+    assert!(matches!(game.game_state.win_state, WinState::Ongoing));
+
+    // and the other way round
+    game.flag(0, 0);
+    assert_ne!(game.to_string(), before);
+    assert!(matches!(clone.game_state.win_state, WinState::Won));
+}
+
+#[test]
+fn scripted_full_win() {
+    let args = MinesweeperArgs::builder()
+        .difficulty(Difficulty::Intermediate)
+        .seed(4)
+        .build()
+        .unwrap();
+    let (w, h) = (args.width, args.height);
+    let mut game = Minesweeper::new(args);
+    game.open(w / 2, h / 2);
+
+    // the layout is known once placed: flag the mines around every open number, then chord it
+    let mut progress = true;
+    while progress && matches!(game.game_state.win_state, WinState::Ongoing) {
+        progress = false;
+        for i in 0..game.game_state.cells.len() {
+            let cell = game.game_state.cells[i];
+            if matches!(cell.visibility, Hidden(_)) || cell.content == Empty(0) {
+                continue;
+            }
+            for n in valid_neighbors(&DIRS_8, xy(i, w), w, h) {
+                let neighbor = game.game_state.cells[xy_i(n, w, h).unwrap()];
+                if neighbor.content == Mine && neighbor.visibility == Hidden(Clear) {
+                    game.flag(n.0, n.1);
+                }
+            }
+            let (x, y) = xy(i, w);
+            progress |= game.chord(x, y).is_some();
+        }
+    }
+    let state = &game.game_state;
+    assert!(matches!(state.win_state, WinState::Won));
+    assert_eq!(state.closed_empty_cells, 0);
+    assert_eq!(state.open_mine_cells, 0);
+}
+
+/// Everything undo and redo restore, the clock starts with the first open and stays the same
+/// after it, a mistake stays on the record though
+fn snapshot(state: &GameState) -> String {
+    let mut state = state.clone();
+    state.perfect = false;
+    format!("{state:?}")
+}
+
+#[test]
+fn undo_and_redo_walk_through_the_same_states() {
+    use minesweeper::action::{
+        Action::Debug,
+        DebugAction::{Redo, Undo},
+        GameCommand::{Chord, FlagCell, OpenCell},
+    };
+
+    let args = MinesweeperArgs::builder()
+        .difficulty(Difficulty::Beginner)
+        .seed(11)
+        .build()
+        .unwrap();
+    let mut game = Minesweeper::new(args);
+    game.open(4, 4);
+    let mut states = vec![snapshot(&game.game_state)];
+    let commands = [
+        FlagCell((6, 3)),
+        Chord((5, 3)),
+        FlagCell((0, 0)),
+        FlagCell((0, 0)),
+        OpenCell((1, 2)),
+        // a mine, losing the game
+        OpenCell((2, 0)),
+    ];
+    for command in commands {
+        assert!(game.play(command).is_some());
+        states.push(snapshot(&game.game_state));
+    }
+    assert!(matches!(game.game_state.win_state, WinState::Lost));
+    assert_eq!(game.game_state.moves, 7);
+
+    for expected in states.iter().rev().skip(1) {
+        game.input_state.action = Some(Debug(Undo));
+        game.update();
+        assert_eq!(&snapshot(&game.game_state), expected);
+    }
+    game.input_state.action = Some(Debug(Undo));
+    game.update();
+    game.input_state.action = Some(Debug(Redo));
+    game.update();
+    assert_eq!(snapshot(&game.game_state), states[0]);
+
+    for expected in &states[1..] {
+        game.input_state.action = Some(Debug(Redo));
+        assert!(game.update().is_some());
+        assert_eq!(&snapshot(&game.game_state), expected);
+    }
+    game.input_state.action = Some(Debug(Redo));
+    assert!(game.update().is_none());
+}
+
+#[test]
+fn surrender_shows_every_cell_and_undoes_exactly() {
+    use minesweeper::action::{Action::Debug, DebugAction::Undo};
+    use minesweeper::tile_visibility::TileVisibility::Show;
+
+    let args = MinesweeperArgs::builder()
+        .difficulty(Difficulty::Beginner)
+        .seed(11)
+        .build()
+        .unwrap();
+    let mut game = Minesweeper::new(args);
+    // nothing to give up before the mines are placed
+    assert!(game.surrender().is_none());
+    game.open(4, 4);
+    game.flag(6, 3);
+    game.flag(0, 0);
+    let before = snapshot(&game.game_state);
+
+    assert!(game.surrender().is_some());
+    let state = &game.game_state;
+    assert!(state.cells.iter().all(|cell| cell.visibility == Show));
+    assert!(matches!(state.win_state, WinState::Lost));
+    assert_eq!(state.open_mine_cells, 10);
+    assert_eq!(state.closed_empty_cells, 0);
+    assert_eq!(state.flagged_cells, 0);
+
+    game.input_state.action = Some(Debug(Undo));
+    game.update();
+    assert_eq!(snapshot(&game.game_state), before);
+    assert!(matches!(game.game_state.win_state, WinState::Ongoing));
+}
+
+/// Flags the hidden cells in order, up to `count` of them
+fn flag_hidden(game: &mut Minesweeper, count: usize) {
+    let w = game.args.width;
+    let hidden: Vec<usize> = (0..game.game_state.cells.len())
+        .filter(|&i| game.game_state.cells[i].visibility == Hidden(Clear))
+        .take(count)
+        .collect();
+    for i in hidden {
+        let (x, y) = xy(i, w);
+        game.flag(x, y);
+    }
+}
+
+#[test]
+fn capped_flags_stop_at_the_mine_count() {
+    use minesweeper::args::FlagLimit;
+    use minesweeper::flag::Flag::{Flagged, FlaggedMaybe};
+
+    let mut args = MinesweeperArgs::builder()
+        .difficulty(Difficulty::Beginner)
+        .seed(11)
+        .build()
+        .unwrap();
+    args.flag_limit = FlagLimit::Cap;
+    let mut game = Minesweeper::new(args);
+    game.open(4, 4);
+    flag_hidden(&mut game, 10);
+    assert_eq!(game.game_state.flagged_cells, 10);
+
+    let cells = &game.game_state.cells;
+    let free = (0..cells.len()).find(|&i| cells[i].visibility == Hidden(Clear));
+    let (x, y) = xy(free.unwrap(), 9);
+    assert!(game.flag(x, y).is_none());
+    assert_eq!(game.game_state.flagged_cells, 10);
+
+    // a placed flag still turns into a maybe, which makes room for another
+    let cells = &game.game_state.cells;
+    let flagged = (0..cells.len()).find(|&i| cells[i].visibility == Hidden(Flagged));
+    let (fx, fy) = xy(flagged.unwrap(), 9);
+    game.flag(fx, fy);
+    assert_eq!(
+        game.game_state.cells[flagged.unwrap()].visibility,
+        Hidden(FlaggedMaybe)
+    );
+    assert!(game.flag(x, y).is_some());
+    assert_eq!(game.game_state.flagged_cells, 10);
+}
+
+#[test]
+fn warned_flags_go_past_the_mine_count() {
+    let args = MinesweeperArgs::builder()
+        .difficulty(Difficulty::Beginner)
+        .seed(11)
+        .build()
+        .unwrap();
+    let mut game = Minesweeper::new(args);
+    game.open(4, 4);
+    flag_hidden(&mut game, 12);
+    assert_eq!(game.game_state.flagged_cells, 12);
+}
+
+#[test]
+fn open_flagged_cells() {
+    use minesweeper::flag::Flag::Flagged;
+    use minesweeper::tile_visibility::TileVisibility::Show;
+
+    for open_flagged in [false, true] {
+        let mut args = MinesweeperArgs::builder()
+            .difficulty(Difficulty::Beginner)
+            .seed(11)
+            .build()
+            .unwrap();
+        args.open_flagged = open_flagged;
+        let mut game = Minesweeper::new(args);
+        game.open(4, 4);
+        // a safe number and a mine
+        let (safe, mine) = (1, 3 * 9 + 6);
+        assert_eq!(game.game_state.cells[mine].content, Mine);
+        game.flag(1, 0);
+        game.flag(6, 3);
+
+        let opened = game.open(1, 0).is_some();
+        assert_eq!(opened, open_flagged);
+        let expected = if open_flagged { Show } else { Hidden(Flagged) };
+        assert_eq!(game.game_state.cells[safe].visibility, expected);
+        assert_eq!(game.game_state.flagged_cells, 2 - open_flagged as u32);
+
+        let opened = game.open(6, 3).is_some();
+        assert_eq!(opened, open_flagged);
+        let lost = matches!(game.game_state.win_state, WinState::Lost);
+        assert_eq!(lost, open_flagged);
+    }
+}
+
+#[test]
+fn full_game_through_the_public_methods() {
+    let args = MinesweeperArgs::builder()
+        .difficulty(Difficulty::Beginner)
+        .seed(11)
+        .build()
+        .unwrap();
+    let mut game = Minesweeper::new(args);
+    // the mines are placed by the first open
+    assert!(game.flag(0, 0).is_none());
+    assert!(game.chord(4, 4).is_none());
+    assert!(matches!(game.game_state.win_state, WinState::Untouched));
+    let opened = game.open(4, 4).unwrap().indices();
+    assert!(opened.contains(&(4 * 9 + 4)) && opened.len() > 1);
+
+    // the 1 at (5, 3) has a single mine around it, at (6, 3)
+    assert!(game.chord(5, 3).is_none());
+    assert_eq!(game.flag(6, 3).unwrap().indices(), [3 * 9 + 6]);
+    assert!(!game.chord(5, 3).unwrap().indices().is_empty());
+
+    let w = 9;
+    for i in 0..game.game_state.cells.len() {
+        let cell = game.game_state.cells[i];
+        if cell.content == Mine && cell.visibility == Hidden(Clear) {
+            let (x, y) = xy(i, w);
+            assert!(game.flag(x, y).is_some());
+        }
+    }
+    for i in 0..game.game_state.cells.len() {
+        let (x, y) = xy(i, w);
+        game.chord(x, y);
+    }
+    open_all_empty(&mut game);
+    assert!(matches!(game.game_state.win_state, WinState::Won));
+
+    // a finished game takes no more moves
+    let moves = game.game_state.moves;
+    assert!(game.open(0, 0).is_none());
+    assert!(game.flag(6, 3).is_none());
+    assert!(game.chord(5, 3).is_none());
+    assert!(game.surrender().is_none());
+    assert_eq!(game.game_state.moves, moves);
+    assert_eq!(game.history.applied().len(), moves as usize);
+}
+
+#[test]
+fn mine_percent_steps_are_monotonic_and_bounded() {
+    use minesweeper::action::{Action::Restart, RestartAction::IncrementMinesPercent};
+    use minesweeper::util::Sign::{self, Negative, Positive};
+
+    fn step(game: &mut Minesweeper, sign: Sign) -> u32 {
+        game.input_state.action = Some(Restart(Some(IncrementMinesPercent(sign))));
+        game.update();
+        game.args.mines
+    }
+
+    for (w, h) in [(8, 8), (32, 16), (100, 100)] {
+        let args = MinesweeperArgs::builder()
+            .width(w)
+            .height(h)
+            .mines(1)
+            .build()
+            .unwrap();
+        let mut game = Minesweeper::new(args);
+        let size = w as u32 * h as u32;
+        let max = size - 9;
+        // a step is a whole percent of the board, or a single mine where that's less
+        let largest_step = size.div_ceil(100) + 1;
+
+        let mut mines = game.args.mines;
+        let mut up = vec![mines];
+        while mines < max {
+            let next = step(&mut game, Positive);
+            assert!(
+                next > mines && next - mines <= largest_step,
+                "{w}x{h}: {mines} -> {next}"
+            );
+            mines = next;
+            up.push(mines);
+        }
+        assert_eq!(mines, max);
+        assert_eq!(step(&mut game, Positive), max);
+        assert!(up.len() <= 101, "{w}x{h} took {} steps", up.len());
+
+        while mines > 1 {
+            let next = step(&mut game, Negative);
+            assert!(
+                next < mines && mines - next <= largest_step,
+                "{w}x{h}: {mines} -> {next}"
+            );
+            mines = next;
+        }
+        assert_eq!(step(&mut game, Negative), 1);
+    }
+}
+
+#[test]
+fn auto_complete_keeps_a_no_flag_win() {
+    for win_on_flags in [false, true] {
+        let mut args = MinesweeperArgs::builder()
+            .difficulty(Difficulty::Beginner)
+            .seed(11)
+            .build()
+            .unwrap();
+        args.auto_complete = true;
+        args.win_on_flags = win_on_flags;
+        let mut game = Minesweeper::new(args);
+        game.open(4, 4);
+        open_all_empty(&mut game);
+        assert!(matches!(game.game_state.win_state, WinState::Won));
+        assert!(game.game_state.nf_valid());
+    }
+}
+
+#[test]
+fn auto_complete_only_flags_when_flags_win() {
+    use minesweeper::action::{Action::Debug, DebugAction::Undo};
+    use minesweeper::flag::Flag::Flagged;
+
+    for win_on_flags in [false, true] {
+        let mut args = MinesweeperArgs::builder()
+            .difficulty(Difficulty::Beginner)
+            .seed(11)
+            .build()
+            .unwrap();
+        args.auto_complete = true;
+        args.win_on_flags = win_on_flags;
+        let mut game = Minesweeper::new(args);
+        game.open(4, 4);
+        open_all_empty(&mut game);
+        let state = &game.game_state;
+        assert!(matches!(state.win_state, WinState::Won));
+        let mines = mine_cells(&game);
+        let flag = if win_on_flags { Flagged } else { Clear };
+        assert!(
+            mines
+                .iter()
+                .all(|&i| state.cells[i].visibility == Hidden(flag))
+        );
+        assert_eq!(state.flagged_cells, if win_on_flags { 10 } else { 0 });
+
+        // the last open and the flags are one move
+        game.input_state.action = Some(Debug(Undo));
+        game.update();
+        assert!(matches!(game.game_state.win_state, WinState::Ongoing));
+        assert_eq!(game.game_state.flagged_cells, 0);
+    }
+}
@@ -0,0 +1,97 @@
+//! Games played from a fixed seed, compared to the snapshots in `tests/golden/`.
+//!
+//! After an intentional change to the board generation, the flood fill or the counters,
+//! rewrite the snapshots with `UPDATE_GOLDEN=1 cargo test --test golden` and review the diff.
+
+use minesweeper::action::{Action::Debug, DebugAction::Undo};
+use minesweeper::args::{Difficulty, MinesweeperArgs};
+use minesweeper::minesweeper::{GameState, Minesweeper};
+use std::fs;
+use std::path::Path;
+
+fn check_golden(name: &str, actual: &str) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path).unwrap_or_default();
+    assert!(
+        expected == actual,
+        "{} differs, rerun with UPDATE_GOLDEN=1 if the change is intended\n{actual}",
+        path.display()
+    );
+}
+
+/// The state as pretty json, but with a line per cell to keep it short
+fn snapshot(state: &GameState) -> String {
+    let mut value = serde_json::to_value(state).unwrap();
+    value["cells"] = serde_json::Value::Null;
+    let cells: Vec<String> = state
+        .cells
+        .iter()
+        .map(|cell| serde_json::to_string(cell).unwrap())
+        .collect();
+    let cells = format!("[\n    {}\n  ]", cells.join(",\n    "));
+    let pretty = serde_json::to_string_pretty(&value).unwrap();
+    pretty.replacen("\"cells\": null", &format!("\"cells\": {cells}"), 1) + "\n"
+}
+
+fn beginner(seed: u64) -> Minesweeper {
+    let args = MinesweeperArgs::builder()
+        .difficulty(Difficulty::Beginner)
+        .seed(seed)
+        .build()
+        .unwrap();
+    Minesweeper::new(args)
+}
+
+#[test]
+fn replay() {
+    let mut game = beginner(11);
+    game.open(4, 4);
+    game.flag(6, 3);
+    game.chord(5, 3);
+    // a maybe flag on an empty cell
+    game.flag(0, 0);
+    game.flag(0, 0);
+    game.open(1, 2);
+    game.open(0, 8);
+    game.input_state.action = Some(Debug(Undo));
+    game.update();
+    game.open(1, 8);
+
+    check_golden("replay.json", &snapshot(&game.game_state));
+}
+
+#[test]
+fn display_mid_game() {
+    let mut args = MinesweeperArgs::builder()
+        .difficulty(Difficulty::Beginner)
+        .seed(11)
+        .build()
+        .unwrap();
+    args.practice = true;
+    let mut game = Minesweeper::new(args);
+    game.open(4, 4);
+    game.flag(6, 3);
+    game.flag(0, 0);
+    game.flag(0, 0);
+    // a mine, only a mistake in practice
+    game.open(2, 2);
+    check_golden("display_mid_game.txt", &game.to_string());
+}
+
+#[test]
+fn display_lost() {
+    let mut game = beginner(11);
+    game.open(4, 4);
+    game.flag(6, 3);
+    game.flag(1, 0);
+    game.flag(1, 0);
+    game.open(2, 0);
+    check_golden("display_lost.txt", &game.to_string());
+}
@@ -0,0 +1,68 @@
+use minesweeper::args::MinesweeperArgs;
+use minesweeper::minesweeper::Minesweeper;
+use minesweeper::spectate::{self, Outgoing, Server};
+use std::net::TcpStream;
+use std::thread;
+use std::time::{Duration, Instant};
+
+fn game(width: u16, height: u16) -> Minesweeper {
+    let args = MinesweeperArgs::builder()
+        .width(width)
+        .height(height)
+        .seed(9)
+        .build()
+        .unwrap();
+    Minesweeper::new(args)
+}
+
+/// Accepts until `count` watchers are connected, connections arrive on another thread
+fn accept(server: &mut Server, game: &Minesweeper, count: usize) {
+    let start = Instant::now();
+    while server.watchers() < count {
+        assert!(
+            start.elapsed() < Duration::from_secs(5),
+            "no watcher connected"
+        );
+        server.accept(game);
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn watcher_mirrors_the_game() {
+    let mut host = game(16, 16);
+    let mut server = Server::bind("127.0.0.1:0").unwrap();
+    let rx = spectate::watch(server.local_addr()).unwrap();
+    accept(&mut server, &host, 1);
+
+    host.open(8, 8);
+    let diff = host.history.last_applied().unwrap();
+    server.broadcast(&Outgoing::Apply(diff));
+
+    let mut mirror = game(8, 8);
+    for _ in 0..2 {
+        let message = rx.recv_timeout(Duration::from_secs(5)).unwrap().unwrap();
+        message.apply_to(&mut mirror);
+    }
+    assert_eq!(mirror.to_string(), host.to_string());
+}
+
+#[test]
+fn stalled_watcher_does_not_hold_up_the_game() {
+    let mut host = game(16, 16);
+    host.open(8, 8);
+    let mut server = Server::bind("127.0.0.1:0").unwrap();
+    // connected but never reading
+    let _stalled = TcpStream::connect(server.local_addr()).unwrap();
+    accept(&mut server, &host, 1);
+
+    // once the socket buffers are full the watcher falls behind, writing inline would block there
+    let diff = host.history.last_applied().unwrap();
+    let start = Instant::now();
+    let mut sent = 0;
+    while server.watchers() > 0 {
+        assert!(start.elapsed() < Duration::from_secs(20), "{sent} sent");
+        server.broadcast(&Outgoing::Apply(diff));
+        sent += 1;
+    }
+}
@@ -0,0 +1,53 @@
+use minesweeper::util::{i_xy, xy_i};
+
+#[test]
+fn coordinates_round_trip() {
+    for (w, h) in [
+        (1, 1),
+        (1, 7),
+        (7, 1),
+        (3, 5),
+        (5, 3),
+        (8, 8),
+        (9, 16),
+        (30, 16),
+    ] {
+        for y in 0..h {
+            for x in 0..w {
+                let i = xy_i((x, y), w, h).unwrap();
+                assert!(i < w as usize * h as usize);
+                assert_eq!(i_xy(i, w, h), Some((x, y)), "{x},{y} on {w}x{h}");
+            }
+        }
+        for i in 0..w as usize * h as usize {
+            let (x, y) = i_xy(i, w, h).unwrap();
+            assert_eq!(xy_i((x, y), w, h), Some(i), "{i} on {w}x{h}");
+        }
+    }
+}
+
+#[test]
+fn coordinates_out_of_bounds() {
+    for (w, h) in [(1, 1), (3, 5), (5, 3), (30, 16)] {
+        assert_eq!(xy_i((w, 0), w, h), None);
+        assert_eq!(xy_i((0, h), w, h), None);
+        assert_eq!(xy_i((w, h), w, h), None);
+        assert_eq!(xy_i((u16::MAX, u16::MAX), w, h), None);
+        assert_eq!(i_xy(w as usize * h as usize, w, h), None);
+        assert_eq!(i_xy(usize::MAX, w, h), None);
+    }
+    // a swapped width and height is out of bounds on a board that isn't square
+    assert_eq!(xy_i((4, 0), 3, 5), None);
+    assert_eq!(xy_i((0, 4), 5, 3), None);
+    assert_eq!(xy_i((0, 0), 0, 5), None);
+    assert_eq!(i_xy(0, 0, 5), None);
+}
+
+#[test]
+fn coordinates_at_the_largest_board() {
+    let side = minesweeper::args::MAX_SIZE;
+    let last = side as usize * side as usize - 1;
+    assert_eq!(xy_i((side - 1, side - 1), side, side), Some(last));
+    assert_eq!(i_xy(last, side, side), Some((side - 1, side - 1)));
+    assert_eq!(i_xy(last + 1, side, side), None);
+}